@@ -0,0 +1,15 @@
+use commonlibsse_ng::re::ActorValues::{ActorValue, ActorValueModifier};
+use commonlibsse_ng::re::PlayerCharacter::PlayerCharacter;
+
+/// How much `actor_value` of the player is below its max. (e.g. lost health)
+pub fn get_player_missing_actor_value(actor_value: ActorValue) -> Option<f32> {
+    let pc = PlayerCharacter::get_singleton()?;
+    let owner = pc.__base.__base.as_actor_value_owner();
+
+    let max = owner.get_permanent_actor_value(actor_value)
+        + pc.__base
+            .__base
+            .get_actor_value_modifier(ActorValueModifier::Temporary, actor_value);
+    let current = owner.get_actor_value(actor_value);
+    Some((max - current).max(0.0))
+}
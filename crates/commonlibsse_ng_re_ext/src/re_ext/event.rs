@@ -0,0 +1,65 @@
+use commonlibsse_ng::re::BSTEvent::{BSEventNotifyControl, BSTEventSink, BSTEventSource};
+use commonlibsse_ng::re::ScriptEventSourceHolder::ScriptEventSourceHolder;
+use commonlibsse_ng::re::TESContainerChangedEvent::TESContainerChangedEvent;
+
+/// `BSTEventSink<E>` that calls a Rust fn.
+///
+/// The game keeps the pointer of a registered sink, so it is leaked and never freed.
+#[repr(C)]
+pub struct FnEventSink<E: 'static> {
+    vtable: &'static FnEventSinkVtbl<E>,
+    callback: fn(&E),
+}
+
+/// Same layout as the C++ vtable of `BSTEventSink<E>`.
+#[repr(C)]
+struct FnEventSinkVtbl<E: 'static> {
+    /// `virtual ~BSTEventSink()` (scalar deleting destructor)
+    destructor: unsafe extern "C" fn(this: *mut FnEventSink<E>, flags: u32) -> *mut FnEventSink<E>,
+    process_event: unsafe extern "C" fn(
+        this: *mut FnEventSink<E>,
+        event: *const E,
+        source: *mut BSTEventSource<E>,
+    ) -> BSEventNotifyControl,
+}
+
+impl<E: 'static> FnEventSink<E> {
+    const VTABLE: &'static FnEventSinkVtbl<E> = &FnEventSinkVtbl {
+        destructor: Self::destructor,
+        process_event: Self::process_event,
+    };
+
+    /// Allocate a sink that lives until the game exits.
+    fn leak(callback: fn(&E)) -> *mut BSTEventSink<E> {
+        let sink = Box::leak(Box::new(Self {
+            vtable: Self::VTABLE,
+            callback,
+        }));
+        (sink as *mut Self).cast()
+    }
+
+    /// Never freed. See the type doc.
+    unsafe extern "C" fn destructor(this: *mut Self, _flags: u32) -> *mut Self {
+        this
+    }
+
+    unsafe extern "C" fn process_event(
+        this: *mut Self,
+        event: *const E,
+        _source: *mut BSTEventSource<E>,
+    ) -> BSEventNotifyControl {
+        if let (Some(this), Some(event)) = unsafe { (this.as_ref(), event.as_ref()) } {
+            (this.callback)(event);
+        }
+        BSEventNotifyControl::Continue
+    }
+}
+
+/// Call `callback` on every item transfer between containers. (including the player's inventory)
+///
+/// Returns `None` if the event source is not ready yet. Call this after `DataLoaded`.
+pub fn add_container_changed_event_sink(callback: fn(&TESContainerChangedEvent)) -> Option<()> {
+    let holder = ScriptEventSourceHolder::get_singleton()?;
+    holder.add_event_sink::<TESContainerChangedEvent>(FnEventSink::leak(callback));
+    Some(())
+}
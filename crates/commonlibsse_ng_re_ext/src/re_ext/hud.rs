@@ -0,0 +1,15 @@
+use core::ffi::{CStr, c_char};
+
+#[commonlibsse_ng::relocate_fn(se_id = 52050, ae_id = 52933)]
+#[inline]
+fn debug_notification_impl(
+    message: *const c_char,
+    sound: *const c_char,
+    cancel_if_already_queued: bool,
+) {
+}
+
+/// Show `message` at the top-left of the HUD. (Same as papyrus `Debug.Notification`)
+pub fn debug_notification(message: &CStr) {
+    debug_notification_impl(message.as_ptr(), core::ptr::null(), true);
+}
@@ -1,17 +1,23 @@
 use core::ptr::NonNull;
 
 use commonlibsse_ng::re::Actor::Actor;
+use commonlibsse_ng::re::BSCoreTypes::FormID;
 use commonlibsse_ng::re::BSTList::BSSimpleList;
 use commonlibsse_ng::re::EnchantmentItem::EnchantmentItem;
+use commonlibsse_ng::re::ExtraCharge::ExtraCharge;
 use commonlibsse_ng::re::ExtraDataList::ExtraDataList;
 use commonlibsse_ng::re::ExtraDataType::ExtraDataType;
 use commonlibsse_ng::re::ExtraEnchantment::ExtraEnchantment;
 use commonlibsse_ng::re::ExtraHealth::ExtraHealth;
 use commonlibsse_ng::re::ExtraUniqueID::ExtraUniqueID;
+use commonlibsse_ng::re::FormTypes::FormType;
 use commonlibsse_ng::re::InventoryEntryData::InventoryEntryData;
 use commonlibsse_ng::re::InventoryMenu::InventoryMenu;
 use commonlibsse_ng::re::PlayerCharacter::PlayerCharacter;
-use commonlibsse_ng::re::TESForm::DerivedTESForm;
+use commonlibsse_ng::re::TESEnchantableForm::TESEnchantableForm;
+use commonlibsse_ng::re::TESForm::{DerivedTESForm, TESForm};
+use commonlibsse_ng::re::TESNPC::TESNPC;
+use commonlibsse_ng::re::TESObjectARMO::TESObjectARMO;
 use commonlibsse_ng::re::TESObjectWEAP::TESObjectWEAP;
 
 #[commonlibsse_ng::relocate_fn(se_id = 11437, ae_id = 11583)]
@@ -26,13 +32,14 @@ pub fn get_entry_enchant_and_health(
     let Some(extra_lists) = inventory.extraLists.as_ref() else {
         return ret;
     };
-    let found_enchant = false;
-    let found_health = false;
+    let mut found_enchant = false;
+    let mut found_health = false;
 
     for extra_list in extra_lists.iter() {
         if extra_list.has_type(ExtraDataType::Enchantment) {
             if let Some(ex_enchant) = extra_list.get_by_type_as::<ExtraEnchantment>() {
                 ret.0 = unsafe { ex_enchant.as_ref() }.enchantment;
+                found_enchant = true;
                 if found_health {
                     return ret;
                 }
@@ -42,6 +49,7 @@ pub fn get_entry_enchant_and_health(
         if extra_list.has_type(ExtraDataType::Health) {
             if let Some(ex_health) = extra_list.get_by_type_as::<ExtraHealth>() {
                 ret.1 = Some(unsafe { ex_health.as_ref() }.health);
+                found_health = true;
                 if found_enchant {
                     return ret;
                 }
@@ -52,6 +60,182 @@ pub fn get_entry_enchant_and_health(
     ret
 }
 
+/// Same as [`get_entry_enchant_and_health`], but only for a single instance.
+pub fn get_extra_list_enchant_and_health(
+    extra_list: &ExtraDataList,
+) -> (Option<NonNull<EnchantmentItem>>, Option<f32>) {
+    let enchant = extra_list
+        .get_by_type_as::<ExtraEnchantment>()
+        .and_then(|ex_enchant| unsafe { ex_enchant.as_ref() }.enchantment);
+    let health = extra_list
+        .get_by_type_as::<ExtraHealth>()
+        .map(|ex_health| unsafe { ex_health.as_ref() }.health);
+    (enchant, health)
+}
+
+/// Returns (remaining charge, max charge) of the enchantment.
+///
+/// Max charge is `Some` only for player enchanted items. For others, use `TESEnchantableForm::amountofEnchantment`.
+pub fn get_extra_list_charge(extra_list: &ExtraDataList) -> (Option<f32>, Option<u16>) {
+    let charge = extra_list
+        .get_by_type_as::<ExtraCharge>()
+        .map(|ex_charge| unsafe { ex_charge.as_ref() }.charge);
+    let max_charge = extra_list
+        .get_by_type_as::<ExtraEnchantment>()
+        .map(|ex_enchant| unsafe { ex_enchant.as_ref() }.charge);
+    (charge, max_charge)
+}
+
+/// `TESEnchantableForm` of a weapon or an armor.
+pub fn get_enchantable_form(form: &TESForm) -> Option<&TESEnchantableForm> {
+    let form = NonNull::from(form);
+    match unsafe { form.as_ref() }.get_form_type() {
+        FormType::Weapon => Some(&unsafe { form.cast::<TESObjectWEAP>().as_ref() }.__base4), // unsafe downcast
+        FormType::Armor => Some(&unsafe { form.cast::<TESObjectARMO>().as_ref() }.__base3), // unsafe downcast
+        _ => None,
+    }
+}
+
+/// Entries of the player's inventory. Empty if the player is not loaded yet.
+///
+/// Do not equip or remove items while iterating. Collect first.
+pub fn player_inventory_entries() -> impl Iterator<Item = &'static InventoryEntryData> {
+    let entries = PlayerCharacter::get_singleton()
+        .and_then(|pc| pc.__base.__base.__base.get_inventory_changes(false))
+        .and_then(|inventory| unsafe { inventory.as_ref()?.entryList.as_ref() });
+
+    entries
+        .into_iter()
+        .flat_map(|entries| entries.iter().filter_map(|entry| unsafe { entry.as_ref() }))
+}
+
+/// Find the entry of `form_id` in the player's inventory.
+pub fn find_player_inventory_entry(form_id: FormID) -> Option<&'static InventoryEntryData> {
+    player_inventory_entries().find(|entry| {
+        entry
+            .get_object()
+            .is_some_and(|object| object.__base.__base.get_form().formID == form_id)
+    })
+}
+
+/// How many the player holds of the item of `inventory`.
+///
+/// `countDelta` is only the change from the player's base container, so the base count is added.
+pub fn get_player_entry_count(inventory: &InventoryEntryData) -> i32 {
+    let base_count = inventory.get_object().map_or(0, |object| {
+        player_base_container_count(object.__base.__base.get_form().formID)
+    });
+    base_count + inventory.countDelta
+}
+
+/// How many of `form_id` the player holds. `0` if none.
+pub fn get_player_item_count(form_id: FormID) -> i32 {
+    match find_player_inventory_entry(form_id) {
+        Some(entry) => get_player_entry_count(entry),
+        None => player_base_container_count(form_id),
+    }
+}
+
+/// Count of `form_id` in the base record of the player. (e.g. the starting gear)
+///
+/// Leveled lists in the container are not resolved.
+fn player_base_container_count(form_id: FormID) -> i32 {
+    let Some(npc) = PlayerCharacter::get_singleton().and_then(|pc| unsafe {
+        pc.__base
+            .__base
+            .__base
+            .data
+            .objectReference
+            .cast::<TESNPC>() // unsafe downcast. The player's base is always an NPC.
+            .as_ref()
+    }) else {
+        return 0;
+    };
+    // TESNPC -> TESActorBase -> TESContainer
+    let container = &npc.__base0.__base2;
+    if container.containerObjects.is_null() {
+        return 0;
+    }
+    let objects = unsafe {
+        core::slice::from_raw_parts(
+            container.containerObjects,
+            container.numContainerObjects as usize,
+        )
+    };
+
+    objects
+        .iter()
+        .filter_map(|object| unsafe { object.as_ref() })
+        .filter(|object| {
+            unsafe { object.obj.as_ref() }
+                .is_some_and(|obj| obj.__base.__base.get_form().formID == form_id)
+        })
+        .map(|object| object.count)
+        .sum()
+}
+
+/// Is this instance equipped? (either hand for weapons)
+#[inline]
+pub fn is_worn(extra_list: &ExtraDataList) -> bool {
+    extra_list.has_type(ExtraDataType::Worn) || extra_list.has_type(ExtraDataType::WornLeft)
+}
+
+/// Unique id of the instance `actor` holds in the hand.
+///
+/// The equipped entry is the whole stack, so the held instance is found by the worn flag of the
+/// hand. `None` if the instance has no unique id.
+pub fn get_equipped_unique_id(actor: &Actor, left: bool) -> Option<u16> {
+    let entry = unsafe { actor.get_equipped_entry_data(left)?.as_ref() };
+    // A shield is armor, so it is `Worn` even in the left hand.
+    let form_type = entry.get_object()?.__base.__base.get_form().get_form_type();
+    let worn = match left && form_type != FormType::Armor {
+        true => ExtraDataType::WornLeft,
+        false => ExtraDataType::Worn,
+    };
+    let extra_list = entry
+        .extraLists
+        .as_ref()?
+        .iter()
+        .find(|extra_list| extra_list.has_type(worn))?;
+    get_extra_list_unique_id(extra_list)
+}
+
+#[inline]
+pub fn get_extra_list_unique_id(extra_list: &ExtraDataList) -> Option<u16> {
+    extra_list
+        .get_by_type_as::<ExtraUniqueID>()
+        .map(|x| unsafe { x.as_ref() }.uniqueID)
+}
+
+/// Extra data list of the instance that has `unique_id`.
+pub fn find_extra_data_list_by_unique_id(
+    inventory: &InventoryEntryData,
+    unique_id: u16,
+) -> Option<&ExtraDataList> {
+    inventory.extraLists.as_ref()?.iter().find(|extra_list| {
+        extra_list.has_type(ExtraDataType::UniqueID)
+            && unsafe {
+                extra_list
+                    .get_by_type_as::<ExtraUniqueID>()
+                    .is_some_and(|x| x.as_ref().uniqueID == unique_id)
+            }
+    })
+}
+
+/// Unique id of the only instance of `inventory` that has one.
+///
+/// `None` if several instances have one, because which of them is meant is unknown. A row of the
+/// inventory menu is a single instance if its extra data differs from the others.
+pub fn get_entry_unique_id(inventory: &InventoryEntryData) -> Option<u16> {
+    let mut unique_ids = inventory
+        .extraLists
+        .as_ref()?
+        .iter()
+        .filter_map(get_extra_list_unique_id);
+    let unique_id = unique_ids.next()?;
+    unique_ids.next().is_none().then_some(unique_id)
+}
+
 #[inline]
 pub fn get_entry_extra_data_lists<'a>(
     ret: &mut Vec<&'a ExtraDataList>,
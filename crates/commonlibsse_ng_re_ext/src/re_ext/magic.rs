@@ -1,16 +1,33 @@
+use core::ptr::NonNull;
+
+use commonlibsse_ng::re::ActorValues::ActorValue;
+use commonlibsse_ng::re::BSCoreTypes::FormID;
 use commonlibsse_ng::re::BSString::BSString;
+use commonlibsse_ng::re::EffectArchetypes::ArchetypeID;
+use commonlibsse_ng::re::GFxValue::GFxValue;
 use commonlibsse_ng::re::ItemCard::ItemCard;
 use commonlibsse_ng::re::MagicItem::MagicItem;
+use commonlibsse_ng::re::MagicMenu::MagicMenu;
+use commonlibsse_ng::re::TESForm::TESForm;
 
 #[commonlibsse_ng::relocate_fn(se_id = 51022, ae_id = 51900)]
 #[inline]
-pub fn get_magic_item_description(
+fn get_magic_item_description_impl(
     item_card: *mut ItemCard,
     magic_item: *mut MagicItem,
-    string: &BSString,
+    string: &mut BSString,
 ) {
 }
 
+/// Returns the effect description shown in the item card. (e.g. `Deals <font color='#FFFFFF'>25</font> points of fire damage.`)
+///
+/// The text contains format codes. Use [`strip_magic_item_description_format_code`] to get plain text.
+pub fn get_magic_item_description(magic_item: *mut MagicItem) -> String {
+    let mut description = BSString::new();
+    get_magic_item_description_impl(core::ptr::null_mut(), magic_item, &mut description);
+    description.as_c_str().to_string_lossy().into_owned()
+}
+
 pub fn strip_magic_item_description_format_code(description: &mut String) {
     while let Some(bracket_lhs) = description.find('<') {
         if let Some(bracket_rhs) = description[bracket_lhs..].find('>') {
@@ -21,3 +38,58 @@ pub fn strip_magic_item_description_format_code(description: &mut String) {
         }
     }
 }
+
+/// Returns the spell, power or shout under the cursor in the magic menu.
+///
+/// Unlike the inventory menu, the native item list is not exposed. So read the form id from the
+/// scaleform item list.
+pub fn get_selected_item_in_magic_menu(magic_menu: &MagicMenu) -> Option<NonNull<TESForm>> {
+    const SELECTED_FORM_ID: &core::ffi::CStr =
+        c"_root.Menu_mc.inventoryLists.itemList.selectedEntry.formId";
+
+    let movie = unsafe { magic_menu.__base.uiMovie.as_ref()? };
+    let mut selection = GFxValue::default();
+    if !movie.get_variable(&mut selection, SELECTED_FORM_ID) || !selection.is_number() {
+        return None;
+    }
+
+    TESForm::lookup_by_id(FormID::new(selection.get_number() as u32))
+}
+
+/// An effect of a potion, poison, scroll or spell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectInfo {
+    pub archetype: ArchetypeID,
+    /// Actor value changed by the effect. (e.g. `Health` for restore health)
+    pub actor_value: ActorValue,
+    /// Magic skill of the effect. (e.g. `Destruction`) `None` for potions and ingredients.
+    pub skill: ActorValue,
+    /// Actor value that resists the effect. (e.g. `ResistFire` for fire damage)
+    pub resist: ActorValue,
+    /// Harmful to the target. (e.g. damage health of poisons)
+    pub detrimental: bool,
+    pub magnitude: f32,
+    /// Seconds. `0` for instant effects.
+    pub duration: u32,
+}
+
+/// Effects of `magic_item`, in the order of the form.
+pub fn get_magic_item_effects(magic_item: &MagicItem) -> Vec<EffectInfo> {
+    magic_item
+        .effects
+        .iter()
+        .filter_map(|effect| unsafe { effect.as_ref() })
+        .filter_map(|effect| {
+            let base = unsafe { effect.baseEffect.as_ref()? };
+            Some(EffectInfo {
+                archetype: base.data.archetype,
+                actor_value: base.data.primaryAV,
+                skill: base.data.associatedSkill,
+                resist: base.data.resistVariable,
+                detrimental: base.is_detrimental(),
+                magnitude: effect.effectItem.magnitude,
+                duration: effect.effectItem.duration,
+            })
+        })
+        .collect()
+}
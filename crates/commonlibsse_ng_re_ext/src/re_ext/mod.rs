@@ -1,5 +1,9 @@
+pub mod actor;
 pub mod equip;
+pub mod event;
+pub mod hud;
 pub mod inventory;
 pub mod magic;
 pub mod math;
+pub mod setting;
 pub mod time;
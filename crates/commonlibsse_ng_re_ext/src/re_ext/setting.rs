@@ -0,0 +1,13 @@
+use std::ffi::CString;
+
+use commonlibsse_ng::re::GameSettingCollection::GameSettingCollection;
+
+/// String game setting (GMST) in the game language. (e.g. `sHealthDataPrefixWeap1` is `Fine` in English)
+///
+/// `None` if it doesn't exist or is not a string.
+pub fn get_game_setting_string(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let setting = GameSettingCollection::get_singleton()?.get_setting(&name)?;
+    let value = setting.get_string()?;
+    Some(value.to_string_lossy().into_owned())
+}
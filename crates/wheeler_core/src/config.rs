@@ -0,0 +1,778 @@
+//! User settings of Wheeler.
+//!
+//! Read from [`CONFIG_PATH`] on SKSE init. Missing file or missing keys fall back to the default values.
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock, RwLockReadGuard};
+
+use snafu::ResultExt as _;
+
+use crate::rendering::color::Color;
+use crate::user_input::controls::{Chord, KeyId, Trigger};
+
+pub const CONFIG_PATH: &str = "Data/SKSE/Plugins/Wheeler/config.toml";
+
+static CONFIG: LazyLock<RwLock<Config>> = LazyLock::new(|| RwLock::new(Config::default()));
+
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub icon: IconConfig,
+    pub tooltip: TooltipConfig,
+    pub badge: BadgeConfig,
+    pub consumables: ConsumablesConfig,
+    pub controls: ControlsConfig,
+    pub while_open: WhileOpenConfig,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct IconConfig {
+    /// Sub directory name of `resources/icons`. `default` is `resources/icons` itself.
+    pub theme: String,
+}
+
+impl Default for IconConfig {
+    fn default() -> Self {
+        Self {
+            theme: crate::rendering::texture_manager::DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+/// Description panel of the hovered entry.
+///
+/// Sizes are in pixels at 1080p, scaled by the resolution.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct TooltipConfig {
+    pub enabled: bool,
+    pub anchor: TooltipAnchor,
+    /// `[x, y]` from the anchor. For `wheel_left`, `x` goes to the left.
+    pub offset: [f32; 2],
+    /// Panel width. Used only when `wrap` is enabled.
+    pub width: f32,
+    /// Wrap long text at `width`. If `false`, the panel is as wide as the longest line.
+    pub wrap: bool,
+    /// Draw colors of the description (e.g. magnitudes). If `false`, plain text.
+    pub rich_text: bool,
+    pub background_alpha: f32,
+}
+
+impl Default for TooltipConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            anchor: TooltipAnchor::WheelRight,
+            offset: [40.0, 0.0],
+            width: 420.0,
+            wrap: true,
+            rich_text: true,
+            background_alpha: 0.75,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TooltipAnchor {
+    /// Right side of the wheel, vertically centered.
+    WheelRight,
+    /// Left side of the wheel, vertically centered.
+    WheelLeft,
+    /// `offset` from the top-left of the screen.
+    Fixed,
+}
+
+/// Stack count and charge bar on slots.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct BadgeConfig {
+    /// Count of potions, poisons, scrolls and ammo.
+    pub count: bool,
+    /// Enchantment charge of weapons.
+    pub charge_bar: bool,
+    pub corner: BadgeCorner,
+    /// Multiplied to the default font size.
+    pub font_scale: f32,
+    pub text_color: Color,
+    pub background_color: Color,
+    pub charge_color: Color,
+    pub charge_background_color: Color,
+}
+
+impl Default for BadgeConfig {
+    fn default() -> Self {
+        Self {
+            count: true,
+            charge_bar: true,
+            corner: BadgeCorner::BottomRight,
+            font_scale: 1.0,
+            text_color: Color::WHITE,
+            background_color: Color::rgba(0x00, 0x00, 0x00, 0xA0),
+            charge_color: Color::rgb(0x6A, 0x8F, 0xE0),
+            charge_background_color: Color::rgba(0x00, 0x00, 0x00, 0xA0),
+        }
+    }
+}
+
+/// Corner of the slot where the count badge is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Rules of smart entries, which use the best matching item of the inventory at activation.
+///
+/// In the edit mode, `add_smart_item` adds the first rule matching the item selected in the menu.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ConsumablesConfig {
+    pub rules: Vec<ConsumableRule>,
+}
+
+impl Default for ConsumablesConfig {
+    fn default() -> Self {
+        use ConsumableKind::{Arrow, Bolt, Potion};
+        use EffectKind::{RestoreHealth, RestoreMagicka, RestoreStamina};
+        use SortKey::{Strongest, WeakestSufficient};
+
+        Self {
+            rules: vec![
+                ConsumableRule::new(
+                    "Health potion",
+                    Potion,
+                    Some(RestoreHealth),
+                    WeakestSufficient,
+                ),
+                ConsumableRule::new(
+                    "Magicka potion",
+                    Potion,
+                    Some(RestoreMagicka),
+                    WeakestSufficient,
+                ),
+                ConsumableRule::new(
+                    "Stamina potion",
+                    Potion,
+                    Some(RestoreStamina),
+                    WeakestSufficient,
+                ),
+                ConsumableRule::new("Best arrow", Arrow, None, Strongest),
+                ConsumableRule::new("Best bolt", Bolt, None, Strongest),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ConsumableRule {
+    /// Shown as the title of the entry.
+    pub name: String,
+    pub kind: ConsumableKind,
+    /// Effect the item must have. `None` accepts any. (Ammo has no effect)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effect: Option<EffectKind>,
+    pub sort: SortKey,
+}
+
+impl ConsumableRule {
+    pub fn new(
+        name: &str,
+        kind: ConsumableKind,
+        effect: Option<EffectKind>,
+        sort: SortKey,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            effect,
+            sort,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsumableKind {
+    Potion,
+    Poison,
+    Food,
+    Arrow,
+    Bolt,
+}
+
+/// Effect archetypes of potions and poisons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+    RestoreHealth,
+    RestoreMagicka,
+    RestoreStamina,
+    FortifyHealth,
+    FortifyMagicka,
+    FortifyStamina,
+    ResistFire,
+    ResistFrost,
+    ResistShock,
+    ResistMagic,
+    ResistPoison,
+    Invisibility,
+    WaterBreathing,
+    DamageHealth,
+    Paralysis,
+}
+
+/// Which of the matching items is used. Strength is the magnitude, or the damage for ammo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Strongest,
+    Weakest,
+    /// The weakest that fully restores the lost amount, or the strongest if none does.
+    ///
+    /// Same as `strongest` for effects other than restore.
+    WeakestSufficient,
+}
+
+/// Keys of the actions. Keys are names or numbers of [`KeyId`]s. (e.g. `"LShift"`, `"Gamepad LB"`)
+///
+/// Actions other than `toggle_wheel` work only while the wheel is open.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ControlsConfig {
+    /// Open the wheel, or close it if open.
+    pub toggle_wheel: KeyBinding,
+    /// Equip the hovered item to the right hand. Loadouts ignore the hand.
+    pub activate_right: KeyBinding,
+    /// Equip the hovered item to the left hand.
+    pub activate_left: KeyBinding,
+    /// Select the next item of the hovered entry. (Mouse wheel also works)
+    pub next_item: KeyBinding,
+    /// Select the previous item of the hovered entry.
+    pub prev_item: KeyBinding,
+    /// Show the next wheel while the wheel is open.
+    pub next_wheel: KeyBinding,
+    /// Show the previous wheel while the wheel is open.
+    pub prev_wheel: KeyBinding,
+    /// Switch the edit mode. The following actions work only in the edit mode.
+    pub toggle_edit_mode: KeyBinding,
+    /// Add the item selected in the inventory/magic menu to the hovered entry.
+    pub add_item: KeyBinding,
+    /// Add the current weapons, spells, ammo and shout as a loadout to the hovered entry.
+    pub add_loadout: KeyBinding,
+    /// Add the worn armor and clothing as an armor set to the hovered entry.
+    pub add_armor_set: KeyBinding,
+    /// Switch whether the hovered armor set unequips armor in the slots it doesn't cover.
+    pub toggle_unequip_uncovered: KeyBinding,
+    /// Add a smart entry of the first consumable rule that the selected item matches.
+    pub add_smart_item: KeyBinding,
+    /// Remove the shown item of the hovered entry.
+    pub remove_item: KeyBinding,
+    /// Insert an empty entry after the hovered entry.
+    pub insert_entry: KeyBinding,
+    pub remove_entry: KeyBinding,
+    /// Add an empty wheel after the active wheel.
+    pub add_wheel: KeyBinding,
+    pub remove_wheel: KeyBinding,
+    /// Undo the last edit.
+    pub undo: KeyBinding,
+    /// Redo the last undone edit.
+    pub redo: KeyBinding,
+    /// Show the window to change these keys.
+    pub toggle_settings: KeyBinding,
+    /// Type a name to find items in all wheels. Enter equips the top match, Escape stops.
+    pub search: KeyBinding,
+    pub gestures: GestureConfig,
+    pub number_keys: NumberKeysConfig,
+}
+
+/// Generate [`ControlsConfig::ACTIONS`] and the lookups by action name from the field list.
+macro_rules! control_actions {
+    ($($action:ident),* $(,)?) => {
+        impl ControlsConfig {
+            /// Names of the actions. Same as the keys of the config file.
+            pub const ACTIONS: &[&str] = &[$(stringify!($action)),*];
+
+            pub fn binding(&self, action: &str) -> Option<&KeyBinding> {
+                match action {
+                    $(stringify!($action) => Some(&self.$action),)*
+                    _ => None,
+                }
+            }
+
+            pub fn binding_mut(&mut self, action: &str) -> Option<&mut KeyBinding> {
+                match action {
+                    $(stringify!($action) => Some(&mut self.$action),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+control_actions!(
+    toggle_wheel,
+    activate_right,
+    activate_left,
+    next_item,
+    prev_item,
+    next_wheel,
+    prev_wheel,
+    toggle_edit_mode,
+    add_item,
+    add_loadout,
+    add_armor_set,
+    toggle_unequip_uncovered,
+    add_smart_item,
+    remove_item,
+    insert_entry,
+    remove_entry,
+    add_wheel,
+    remove_wheel,
+    undo,
+    redo,
+    toggle_settings,
+    search,
+);
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        Self {
+            toggle_wheel: KeyBinding::keyboard(0x22), // G
+            activate_right: KeyBinding {
+                keyboard: Some(KeyId(257)), // Mouse Right
+                gamepad: Some(KeyId(281)),  // RT
+                ..Default::default()
+            },
+            activate_left: KeyBinding {
+                keyboard: Some(KeyId(256)), // Mouse Left
+                gamepad: Some(KeyId(280)),  // LT
+                ..Default::default()
+            },
+            next_item: KeyBinding::gamepad(269),  // DPad Right
+            prev_item: KeyBinding::gamepad(268),  // DPad Left
+            next_wheel: KeyBinding::gamepad(267), // DPad Down
+            prev_wheel: KeyBinding::gamepad(266), // DPad Up
+            toggle_edit_mode: KeyBinding::keyboard(0xC7), // Home
+            add_item: KeyBinding::keyboard(0xD2), // Insert
+            add_loadout: KeyBinding::keyboard(0x26), // L
+            add_armor_set: KeyBinding::keyboard(0x18), // O
+            toggle_unequip_uncovered: KeyBinding::keyboard(0x16), // U
+            add_smart_item: KeyBinding::keyboard(0x19), // P
+            remove_item: KeyBinding::keyboard(0xD3), // Delete
+            insert_entry: KeyBinding::keyboard(0xC9), // Page Up
+            remove_entry: KeyBinding::keyboard(0xD1), // Page Down
+            add_wheel: KeyBinding::keyboard(0x4E), // Numpad +
+            remove_wheel: KeyBinding::keyboard(0x4A), // Numpad -
+            undo: KeyBinding::keyboard(0x0E),     // Backspace
+            redo: KeyBinding::keyboard(0xCF),     // End
+            toggle_settings: KeyBinding::keyboard(0x3B), // F1
+            search: KeyBinding::keyboard(0x35),   // Slash
+            gestures: GestureConfig::default(),
+            number_keys: NumberKeysConfig::default(),
+        }
+    }
+}
+
+/// `None` means unbound.
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct KeyBinding {
+    /// Keyboard or mouse
+    pub keyboard: Option<KeyId>,
+    /// Keys to hold with `keyboard`. (e.g. `["LShift"]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keyboard_modifiers: Vec<KeyId>,
+    pub gamepad: Option<KeyId>,
+    /// Buttons to hold with `gamepad`. (e.g. `["Gamepad LB"]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub gamepad_modifiers: Vec<KeyId>,
+    /// Press, release or a gesture. (e.g. `double_tap`)
+    pub on: Trigger,
+}
+
+impl KeyBinding {
+    #[inline]
+    pub const fn keyboard(key: u32) -> Self {
+        Self {
+            keyboard: Some(KeyId(key)),
+            keyboard_modifiers: Vec::new(),
+            gamepad: None,
+            gamepad_modifiers: Vec::new(),
+            on: Trigger::Down,
+        }
+    }
+
+    #[inline]
+    pub const fn gamepad(key: u32) -> Self {
+        Self {
+            keyboard: None,
+            keyboard_modifiers: Vec::new(),
+            gamepad: Some(KeyId(key)),
+            gamepad_modifiers: Vec::new(),
+            on: Trigger::Down,
+        }
+    }
+
+    /// Keyboard and gamepad chords that are bound.
+    pub fn chords(&self) -> impl Iterator<Item = Chord> {
+        [
+            self.chord(DeviceSlot::Keyboard),
+            self.chord(DeviceSlot::Gamepad),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn chord(&self, slot: DeviceSlot) -> Option<Chord> {
+        let (key, modifiers) = match slot {
+            DeviceSlot::Keyboard => (self.keyboard, &self.keyboard_modifiers),
+            DeviceSlot::Gamepad => (self.gamepad, &self.gamepad_modifiers),
+        };
+        key.map(|key| Chord::new(modifiers.iter().copied(), key))
+    }
+
+    /// `None` unbinds the slot.
+    pub fn set_chord(&mut self, slot: DeviceSlot, chord: Option<Chord>) {
+        let (key, modifiers) = match slot {
+            DeviceSlot::Keyboard => (&mut self.keyboard, &mut self.keyboard_modifiers),
+            DeviceSlot::Gamepad => (&mut self.gamepad, &mut self.gamepad_modifiers),
+        };
+        *key = chord.as_ref().map(|chord| chord.key);
+        *modifiers = chord
+            .map(|chord| chord.modifiers().to_vec())
+            .unwrap_or_default();
+    }
+}
+
+/// Which key of a [`KeyBinding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceSlot {
+    /// Keyboard or mouse
+    Keyboard,
+    Gamepad,
+}
+
+/// Timing of the gesture [`Trigger`]s, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct GestureConfig {
+    /// Held this long, a press is a long press instead of a tap.
+    pub long_press_secs: f32,
+    /// Max time from a tap to the next press to make a double tap.
+    pub double_tap_secs: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            long_press_secs: 0.5,
+            double_tap_secs: 0.3,
+        }
+    }
+}
+
+/// Keys `1`-`9` and `0` equip the shown item of the 1st-10th entry while the wheel is open.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct NumberKeysConfig {
+    pub enabled: bool,
+    /// Held with a number key, equips to the left hand instead of the right. (e.g. `"LAlt"`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left_hand_modifier: Option<KeyId>,
+    /// Draw the number of each entry next to its slot.
+    pub show_numbers: bool,
+}
+
+impl NumberKeysConfig {
+    /// Keys of the 1st-10th entry.
+    pub const KEYS: [KeyId; 10] = [
+        KeyId(0x02), // 1
+        KeyId(0x03),
+        KeyId(0x04),
+        KeyId(0x05),
+        KeyId(0x06),
+        KeyId(0x07),
+        KeyId(0x08),
+        KeyId(0x09),
+        KeyId(0x0A), // 9
+        KeyId(0x0B), // 0
+    ];
+
+    /// Label of the number key of the `index`th entry. `None` past the 10th.
+    pub fn label(index: usize) -> Option<String> {
+        Self::KEYS
+            .get(index)
+            .map(|_| ((index + 1) % 10).to_string())
+    }
+
+    /// Index of the entry `chord` activates, and `true` if to the left hand. `None` while disabled.
+    pub fn find_entry(&self, chord: &Chord) -> Option<(usize, bool)> {
+        if !self.enabled {
+            return None;
+        }
+        let index = Self::KEYS.iter().position(|key| *key == chord.key)?;
+        match chord.modifiers() {
+            [] => Some((index, false)),
+            [modifier] if self.left_hand_modifier == Some(*modifier) => Some((index, true)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for NumberKeysConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            left_hand_modifier: Some(KeyId(0x38)), // LAlt
+            show_numbers: true,
+        }
+    }
+}
+
+/// Game user events that still work while the wheel is open. (e.g. walk while choosing)
+///
+/// Names are of the gameplay context of `ControlMap`. Events in no allowed group are blocked,
+/// and so are keys bound to Wheeler.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct WhileOpenConfig {
+    pub movement: EventGroup,
+    pub attacks: EventGroup,
+    pub menus: EventGroup,
+}
+
+impl WhileOpenConfig {
+    /// Does the game see `user_event` while the wheel is open?
+    pub fn is_allowed(&self, user_event: &str) -> bool {
+        [&self.movement, &self.attacks, &self.menus]
+            .iter()
+            .any(|group| group.allow && group.events.iter().any(|event| event == user_event))
+    }
+}
+
+impl Default for WhileOpenConfig {
+    fn default() -> Self {
+        Self {
+            movement: EventGroup::new(
+                true,
+                &[
+                    "Forward",
+                    "Back",
+                    "Strafe Left",
+                    "Strafe Right",
+                    "Move",
+                    "Sprint",
+                    "Run",
+                    "Toggle Always Run",
+                    "Jump",
+                    "Sneak",
+                ],
+            ),
+            attacks: EventGroup::new(
+                false,
+                &[
+                    "Left Attack/Block",
+                    "Right Attack/Block",
+                    "Dual Attack",
+                    "Shout",
+                    "Ready Weapon",
+                ],
+            ),
+            menus: EventGroup::new(
+                false,
+                &[
+                    "Favorites",
+                    "Inventory",
+                    "Stats",
+                    "Map",
+                    "Tween Menu",
+                    "Quick Inventory",
+                    "Quick Magic",
+                    "Quick Stats",
+                    "Quick Map",
+                    "Wait",
+                    "Journal",
+                ],
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct EventGroup {
+    /// Let the game see these events while the wheel is open.
+    pub allow: bool,
+    /// User event names. (e.g. `Forward`)
+    pub events: Vec<String>,
+}
+
+impl EventGroup {
+    fn new(allow: bool, events: &[&str]) -> Self {
+        Self {
+            allow,
+            events: events.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// Read [`CONFIG_PATH`] and replace the current config.
+///
+/// # Errors
+/// If the file exists but cannot be read or parsed. In that case, the current config is kept.
+pub fn load() -> Result<(), ConfigError> {
+    let path = PathBuf::from(CONFIG_PATH);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            tracing::info!("Not found `{CONFIG_PATH}`. Use default config.");
+            return Ok(());
+        }
+        Err(err) => return Err(err).with_context(|_| FailedToReadSnafu { path }),
+    };
+
+    let config = toml::from_str(&text).with_context(|_| FailedToParseSnafu { path })?;
+    *CONFIG
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = config;
+    Ok(())
+}
+
+/// Write the current config to [`CONFIG_PATH`].
+///
+/// # Errors
+/// If the config cannot be serialized or written.
+pub fn save() -> Result<(), ConfigError> {
+    let path = PathBuf::from(CONFIG_PATH);
+    let text = toml::to_string_pretty(&*get()).context(FailedToSerializeSnafu)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|_| FailedToWriteSnafu { path: dir })?;
+    }
+    std::fs::write(&path, text).with_context(|_| FailedToWriteSnafu { path })
+}
+
+/// Change the current config. Call [`save`] to keep the change.
+pub fn update(f: impl FnOnce(&mut Config)) {
+    f(&mut CONFIG
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()));
+}
+
+/// Get the current config.
+pub fn get() -> RwLockReadGuard<'static, Config> {
+    CONFIG
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub enum ConfigError {
+    /// Failed to read config. path: {path:?}, error: {source}
+    FailedToRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// Failed to parse config. path: {path:?}, error: {source}
+    FailedToParse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    /// Failed to serialize config. error: {source}
+    FailedToSerialize { source: toml::ser::Error },
+
+    /// Failed to write config. path: {path:?}, error: {source}
+    FailedToWrite {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const Q: KeyId = KeyId(0x10);
+    const L_SHIFT: KeyId = KeyId(0x2A);
+    const L_CTRL: KeyId = KeyId(0x1D);
+    const PAD_A: KeyId = KeyId(276);
+
+    /// `control_actions!` must list every `KeyBinding` field of `ControlsConfig`.
+    #[test]
+    fn actions_cover_binding_fields() {
+        const OTHER_FIELDS: [&str; 2] = ["gestures", "number_keys"];
+
+        let toml::Value::Table(table) = toml::Value::try_from(ControlsConfig::default()).unwrap()
+        else {
+            panic!("ControlsConfig must be a table");
+        };
+        let mut fields: Vec<_> = table
+            .keys()
+            .map(String::as_str)
+            .filter(|field| !OTHER_FIELDS.contains(field))
+            .collect();
+        fields.sort_unstable();
+        let mut actions = ControlsConfig::ACTIONS.to_vec();
+        actions.sort_unstable();
+        assert_eq!(actions, fields);
+
+        let mut controls = ControlsConfig::default();
+        for action in ControlsConfig::ACTIONS {
+            let binding = controls.binding_mut(action).unwrap();
+            binding.on = Trigger::DoubleTap;
+            assert_eq!(controls.binding(action).unwrap().on, Trigger::DoubleTap);
+        }
+        assert_eq!(controls.binding("gestures"), None);
+    }
+
+    #[test]
+    fn chord_of_binding() {
+        let binding = KeyBinding {
+            keyboard: Some(Q),
+            keyboard_modifiers: vec![L_SHIFT, L_CTRL, L_SHIFT],
+            ..KeyBinding::gamepad(PAD_A.0)
+        };
+        assert_eq!(
+            binding.chord(DeviceSlot::Keyboard),
+            Some(Chord::new([L_CTRL, L_SHIFT], Q))
+        );
+        assert_eq!(binding.chord(DeviceSlot::Gamepad), Some(PAD_A.into()));
+        assert_eq!(binding.chords().count(), 2);
+
+        // Modifiers without a key are no chord.
+        let binding = KeyBinding {
+            keyboard_modifiers: vec![L_SHIFT],
+            ..Default::default()
+        };
+        assert_eq!(binding.chord(DeviceSlot::Keyboard), None);
+        assert_eq!(binding.chords().count(), 0);
+    }
+
+    #[test]
+    fn set_chord_of_binding() {
+        let mut binding = KeyBinding::gamepad(PAD_A.0);
+        let chord = Chord::new([L_SHIFT, L_CTRL], Q);
+
+        binding.set_chord(DeviceSlot::Keyboard, Some(chord.clone()));
+        assert_eq!(binding.keyboard, Some(Q));
+        assert_eq!(binding.keyboard_modifiers, [L_CTRL, L_SHIFT]);
+        assert_eq!(binding.chord(DeviceSlot::Keyboard), Some(chord.clone()));
+        // The other slot is kept.
+        assert_eq!(binding.gamepad, Some(PAD_A));
+
+        binding.set_chord(DeviceSlot::Keyboard, Some(Q.into()));
+        assert_eq!(binding.keyboard_modifiers, []);
+
+        binding.set_chord(DeviceSlot::Keyboard, Some(chord));
+        binding.set_chord(DeviceSlot::Keyboard, None);
+        assert_eq!(binding.keyboard, None);
+        assert_eq!(binding.keyboard_modifiers, []);
+        assert_eq!(binding, KeyBinding::gamepad(PAD_A.0));
+    }
+}
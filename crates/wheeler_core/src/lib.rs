@@ -1,3 +1,4 @@
+pub mod config;
 pub mod hook;
 pub mod rendering;
 pub mod user_input;
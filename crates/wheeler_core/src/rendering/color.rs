@@ -0,0 +1,74 @@
+/// 8bit RGBA color. Written as `#RRGGBB` or `#RRGGBBAA` in config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Self = Self::rgb(0xFF, 0xFF, 0xFF);
+
+    #[inline]
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 0xFF)
+    }
+
+    #[inline]
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Parse `#RRGGBB`, `#RRGGBBAA`. (`#` is optional)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+        match hex.len() {
+            6 => Some(Self::rgb(channel(0)?, channel(2)?, channel(4)?)),
+            8 => Some(Self::rgba(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// `IM_COL32` packed color. (`0xAABBGGRR`)
+    #[inline]
+    pub const fn to_im_u32(self) -> u32 {
+        (self.a as u32) << 24 | (self.b as u32) << 16 | (self.g as u32) << 8 | self.r as u32
+    }
+
+    /// For `ImVec4` colors.
+    #[inline]
+    pub fn to_f32_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|c| c as f32 / 255.0)
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        Self::from_hex(&hex)
+            .ok_or_else(|| format!("Invalid color `{hex}`. Expected `#RRGGBB` or `#RRGGBBAA`"))
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        match color.a {
+            0xFF => format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b),
+            a => format!("#{:02X}{:02X}{:02X}{a:02X}", color.r, color.g, color.b),
+        }
+    }
+}
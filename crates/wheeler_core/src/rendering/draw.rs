@@ -0,0 +1,130 @@
+//! Thin helpers over `imgui::sys` draw calls.
+use imgui::sys::{
+    ImDrawList, ImDrawList_AddImage, ImDrawList_AddRectFilled, ImDrawList_AddText_FontPtr,
+    ImFont_CalcTextSizeA, ImVec2, ImVec4, igGetFont, igGetForegroundDrawList, igImage,
+    igTextUnformatted,
+};
+use windows::core::Interface as _;
+
+use super::color::Color;
+use super::texture_manager::Image;
+
+/// Draw list over all windows. Wheel is drawn here, because its popup window is off-screen.
+#[inline]
+pub(crate) fn foreground_draw_list() -> *mut ImDrawList {
+    unsafe { igGetForegroundDrawList() }
+}
+
+/// Draw `image` fitted into `size` at `center`, multiplied by `color`.
+pub(crate) fn draw_image(
+    draw_list: *mut ImDrawList,
+    image: &Image,
+    center: [f32; 2],
+    size: [f32; 2],
+    color: Color,
+) {
+    let Some(texture) = image.texture.as_ref() else {
+        return;
+    };
+
+    let half = [size[0] * 0.5, size[1] * 0.5];
+    unsafe {
+        ImDrawList_AddImage(
+            draw_list,
+            texture.as_raw(),
+            ImVec2::new(center[0] - half[0], center[1] - half[1]),
+            ImVec2::new(center[0] + half[0], center[1] + half[1]),
+            ImVec2::zero(),
+            ImVec2::new(1.0, 1.0),
+            color.to_im_u32(),
+        );
+    }
+}
+
+/// Filled rectangle from `min` to `max`.
+pub(crate) fn draw_rect_filled(
+    draw_list: *mut ImDrawList,
+    min: [f32; 2],
+    max: [f32; 2],
+    color: Color,
+    rounding: f32,
+) {
+    unsafe {
+        ImDrawList_AddRectFilled(
+            draw_list,
+            ImVec2::new(min[0], min[1]),
+            ImVec2::new(max[0], max[1]),
+            color.to_im_u32(),
+            rounding,
+            0,
+        );
+    }
+}
+
+/// Draw `text` with the current font at `font_size`. `pos` is the top-left.
+pub(crate) fn draw_text(
+    draw_list: *mut ImDrawList,
+    pos: [f32; 2],
+    font_size: f32,
+    color: Color,
+    text: &str,
+) {
+    let range = text.as_bytes().as_ptr_range();
+    unsafe {
+        ImDrawList_AddText_FontPtr(
+            draw_list,
+            igGetFont(),
+            font_size,
+            ImVec2::new(pos[0], pos[1]),
+            color.to_im_u32(),
+            range.start.cast(),
+            range.end.cast(),
+            0.0,
+            core::ptr::null(),
+        );
+    }
+}
+
+/// Size of `text` drawn by [`draw_text`].
+pub(crate) fn calc_text_size(font_size: f32, text: &str) -> [f32; 2] {
+    let range = text.as_bytes().as_ptr_range();
+    let mut size = ImVec2::zero();
+    unsafe {
+        ImFont_CalcTextSizeA(
+            &mut size,
+            igGetFont(),
+            font_size,
+            f32::MAX,
+            0.0,
+            range.start.cast(),
+            range.end.cast(),
+            core::ptr::null_mut(),
+        );
+    }
+    [size.x, size.y]
+}
+
+/// `ImGui::Image` at the cursor of the current window.
+pub(crate) fn draw_image_inline(image: &Image, size: [f32; 2], color: Color) {
+    let Some(texture) = image.texture.as_ref() else {
+        return;
+    };
+
+    let [r, g, b, a] = color.to_f32_array();
+    unsafe {
+        igImage(
+            texture.as_raw(),
+            ImVec2::new(size[0], size[1]),
+            ImVec2::zero(),
+            ImVec2::new(1.0, 1.0),
+            ImVec4::new(r, g, b, a),
+            ImVec4::zero(),
+        );
+    }
+}
+
+/// `ImGui::TextUnformatted` without copying to a null terminated string.
+pub(crate) fn text_unformatted(text: &str) {
+    let range = text.as_bytes().as_ptr_range();
+    unsafe { igTextUnformatted(range.start.cast(), range.end.cast()) };
+}
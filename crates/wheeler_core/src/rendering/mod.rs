@@ -1,2 +1,5 @@
+pub mod color;
+pub mod draw;
 pub mod render_manager;
+pub mod rich_text;
 pub mod texture_manager;
@@ -104,6 +104,9 @@ impl D3DInitHook {
         }
 
         load_custom_font();
+
+        // Textures need `DEVICE`, which is set above.
+        crate::rendering::texture_manager::Texture::init();
     }
 }
 impl Hook<VariantOffset> for D3DInitHook {
@@ -0,0 +1,513 @@
+//! Skyrim description markup. (e.g. `Deals <font color='#FFFFFF'>25</font> points of fire damage.`)
+//!
+//! Supported: `<font color='#RRGGBB'>`, `<b>`, `<br>`, `<p>` and HTML entities (`&lt;`, `&#37;`, ...).
+//! Other tags are dropped, the same as [`strip_magic_item_description_format_code`].
+//!
+//! [`strip_magic_item_description_format_code`]: commonlibsse_ng_re_ext::re_ext::magic::strip_magic_item_description_format_code
+use imgui::sys::{
+    ImDrawList_AddText_Vec2, ImGuiCol_Text, ImVec2, igCalcTextSize, igDummy, igGetColorU32_Col,
+    igGetContentRegionAvail, igGetCursorScreenPos, igGetTextLineHeight, igGetWindowDrawList,
+};
+
+use super::color::Color;
+
+/// Text with the same style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// May contain `\n`.
+    pub text: String,
+    /// `None` means the default text color.
+    pub color: Option<Color>,
+    pub bold: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RichText {
+    pub spans: Vec<Span>,
+}
+
+impl RichText {
+    pub fn parse(markup: &str) -> Self {
+        let mut parser = Parser::default();
+        let mut rest = markup;
+
+        while let Some(tag_start) = rest.find('<') {
+            let Some(tag_len) = rest[tag_start..].find('>') else {
+                break; // Not a tag. Keep `<` as text.
+            };
+            parser.push_text(&rest[..tag_start]);
+            parser.tag(&rest[tag_start + 1..tag_start + tag_len]);
+            rest = &rest[tag_start + tag_len + 1..];
+        }
+        parser.push_text(rest);
+
+        Self {
+            spans: parser.spans,
+        }
+    }
+
+    /// Text without styles.
+    pub fn to_plain_string(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[inline]
+    pub fn is_blank(&self) -> bool {
+        self.spans.iter().all(|span| span.text.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Parser {
+    spans: Vec<Span>,
+    /// `None` is pushed for `<font>` without color, so that `</font>` pops the right one.
+    colors: Vec<Option<Color>>,
+    bold_depth: usize,
+}
+
+impl Parser {
+    fn style(&self) -> (Option<Color>, bool) {
+        let color = self.colors.iter().rev().find_map(|color| *color);
+        (color, self.bold_depth > 0)
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let text = decode_entities(text);
+        let (color, bold) = self.style();
+
+        match self.spans.last_mut() {
+            Some(last) if last.color == color && last.bold == bold => last.text.push_str(&text),
+            _ => self.spans.push(Span { text, color, bold }),
+        }
+    }
+
+    /// `tag`: inside of `<>`
+    fn tag(&mut self, tag: &str) {
+        let tag = tag.trim().trim_end_matches('/').trim_end();
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+
+        match name.to_ascii_lowercase().as_str() {
+            "font" => self.colors.push(parse_color_attribute(attributes)),
+            "/font" => {
+                self.colors.pop();
+            }
+            "b" => self.bold_depth += 1,
+            "/b" => self.bold_depth = self.bold_depth.saturating_sub(1),
+            "br" | "/p" => self.push_text("\n"),
+            _ => {}
+        }
+    }
+}
+
+/// `color='#RRGGBB'` or `color="#RRGGBB"` in the attributes of `<font>`.
+fn parse_color_attribute(attributes: &str) -> Option<Color> {
+    let lower = attributes.to_ascii_lowercase();
+    let value = &attributes[lower.find("color")? + "color".len()..];
+    let value = value.trim_start().strip_prefix('=')?.trim_start();
+
+    let quote = value.chars().next().filter(|c| *c == '\'' || *c == '"');
+    let value = match quote {
+        Some(quote) => {
+            let value = &value[1..];
+            &value[..value.find(quote)?]
+        }
+        None => value.split_whitespace().next()?,
+    };
+    Color::from_hex(value.trim())
+}
+
+/// `&` to `;` of the longest entity we decode. (`&#x10FFFF;`)
+const MAX_ENTITY_LEN: usize = 9;
+
+/// Unknown entities are kept as is.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let entity = rest
+            .find(';')
+            .filter(|semicolon| *semicolon <= MAX_ENTITY_LEN)
+            .and_then(|semicolon| Some((decode_entity(&rest[1..semicolon])?, semicolon)));
+        match entity {
+            Some((c, semicolon)) => {
+                decoded.push(c);
+                rest = &rest[semicolon + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// `name`: between `&` and `;`
+fn decode_entity(name: &str) -> Option<char> {
+    Some(match name {
+        "lt" => '<',
+        "gt" => '>',
+        "amp" => '&',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        _ => {
+            let code = name.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    })
+}
+
+/// A word placed by [`layout`].
+#[derive(Debug, Clone, PartialEq)]
+struct Run<'a> {
+    text: &'a str,
+    /// Offset from the top-left of the text.
+    pos: [f32; 2],
+    color: Option<Color>,
+    bold: bool,
+}
+
+/// Place words of `spans` line by line.
+///
+/// - `wrap_width`: `None` means no wrapping
+/// - `measure`: width of a text
+///
+/// Returns the runs and the size of the whole text.
+fn layout<'a>(
+    spans: &'a [Span],
+    wrap_width: Option<f32>,
+    line_height: f32,
+    measure: impl Fn(&str) -> f32,
+) -> (Vec<Run<'a>>, [f32; 2]) {
+    let mut runs = Vec::new();
+    let [mut x, mut y] = [0.0_f32, 0.0_f32];
+    let mut width = 0.0_f32;
+
+    for span in spans {
+        for (index, line) in span.text.split('\n').enumerate() {
+            if index > 0 {
+                x = 0.0;
+                y += line_height;
+            }
+
+            for word in split_words(line) {
+                let is_space = word.starts_with(is_breaking_space);
+                if is_space && x == 0.0 {
+                    continue; // No leading spaces on a line.
+                }
+
+                let word_width = measure(word);
+                if wrap_width.is_some_and(|wrap_width| x > 0.0 && x + word_width > wrap_width) {
+                    x = 0.0;
+                    y += line_height;
+                    if is_space {
+                        continue;
+                    }
+                }
+
+                if !is_space {
+                    runs.push(Run {
+                        text: word,
+                        pos: [x, y],
+                        color: span.color,
+                        bold: span.bold,
+                    });
+                }
+                x += word_width;
+                width = width.max(x);
+            }
+        }
+    }
+
+    (runs, [width, y + line_height])
+}
+
+/// Split into words and whitespaces, keeping both.
+fn split_words(line: &str) -> impl Iterator<Item = &str> {
+    let mut rest = line;
+    core::iter::from_fn(move || {
+        let is_space = is_breaking_space(rest.chars().next()?);
+        let end = rest
+            .find(|c| is_breaking_space(c) != is_space)
+            .unwrap_or(rest.len());
+
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some(word)
+    })
+}
+
+/// `&nbsp;` is whitespace, but does not break a line.
+#[inline]
+fn is_breaking_space(c: char) -> bool {
+    c.is_whitespace() && c != '\u{a0}'
+}
+
+/// Draw `text` at the cursor of the current window, then advance the cursor.
+///
+/// - `wrap`: wrap at the end of the window content region
+pub(crate) fn draw_rich_text(text: &RichText, wrap: bool) {
+    unsafe {
+        let draw_list = igGetWindowDrawList();
+        let mut origin = ImVec2::zero();
+        igGetCursorScreenPos(&mut origin);
+
+        let wrap_width = wrap.then(|| {
+            let mut available = ImVec2::zero();
+            igGetContentRegionAvail(&mut available);
+            available.x
+        });
+        let default_color = igGetColorU32_Col(ImGuiCol_Text as i32, 1.0);
+
+        let (runs, size) = layout(&text.spans, wrap_width, igGetTextLineHeight(), |word| {
+            let range = word.as_bytes().as_ptr_range();
+            let mut size = ImVec2::zero();
+            igCalcTextSize(&mut size, range.start.cast(), range.end.cast(), false, -1.0);
+            size.x
+        });
+
+        for run in runs {
+            let color = run.color.map_or(default_color, |color| color.to_im_u32());
+            let range = run.text.as_bytes().as_ptr_range();
+            let pos = ImVec2::new(origin.x + run.pos[0], origin.y + run.pos[1]);
+
+            ImDrawList_AddText_Vec2(draw_list, pos, color, range.start.cast(), range.end.cast());
+            if run.bold {
+                // No bold font is loaded. Draw again with 1px offset.
+                let pos = ImVec2::new(pos.x + 1.0, pos.y);
+                ImDrawList_AddText_Vec2(
+                    draw_list,
+                    pos,
+                    color,
+                    range.start.cast(),
+                    range.end.cast(),
+                );
+            }
+        }
+
+        igDummy(ImVec2::new(size[0], size[1]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const RED: Color = Color::rgb(0xFF, 0, 0);
+    const GREEN: Color = Color::rgb(0, 0xFF, 0);
+
+    fn span(text: &str, color: Option<Color>, bold: bool) -> Span {
+        Span {
+            text: text.to_string(),
+            color,
+            bold,
+        }
+    }
+
+    #[test]
+    fn nested_tags() {
+        let text = RichText::parse(
+            "a<font color='#FF0000'>b<b>c<font color=\"#00FF00\">d</font></b>e</font>f",
+        );
+        assert_eq!(
+            text.spans,
+            [
+                span("a", None, false),
+                span("b", Some(RED), false),
+                span("c", Some(RED), true),
+                span("d", Some(GREEN), true),
+                span("e", Some(RED), false),
+                span("f", None, false),
+            ]
+        );
+
+        // `<font>` without color keeps the outer one, and its `</font>` doesn't pop the outer one.
+        let text = RichText::parse(
+            "<font color='#FF0000'>a<font face='$EverywhereFont'>b</font>c</font>d",
+        );
+        assert_eq!(
+            text.spans,
+            [span("abc", Some(RED), false), span("d", None, false)]
+        );
+    }
+
+    #[test]
+    fn line_breaks() {
+        let text = RichText::parse("<p align='center'>One</p><p>Two<br>Three<BR/>Four</p>");
+        assert_eq!(text.to_plain_string(), "One\nTwo\nThree\nFour\n");
+    }
+
+    #[test]
+    fn entities() {
+        let cases = [
+            ("&lt;b&gt;", "<b>"),
+            ("&amp;lt;", "&lt;"),
+            ("5&#37; &#x41;&quot;&apos;", "5% A\"'"),
+            ("A&nbsp;B", "A\u{a0}B"),
+            ("AT&T; &unknown; & ;", "AT&T; &unknown; & ;"),
+            ("&#xFFFFFFFF; &#;", "&#xFFFFFFFF; &#;"),
+        ];
+        for (markup, expected) in cases {
+            assert_eq!(
+                RichText::parse(markup).to_plain_string(),
+                expected,
+                "{markup}"
+            );
+        }
+        // Decoded `<` is not a tag.
+        assert_eq!(
+            RichText::parse("&lt;b&gt;x").spans,
+            [span("<b>x", None, false)]
+        );
+    }
+
+    #[test]
+    fn color_attributes() {
+        let cases = [
+            ("color='#FF0000'", Some(RED)),
+            ("COLOR = \"#ff0000\"", Some(RED)),
+            ("color=#FF0000 face='x'", Some(RED)),
+            ("face='x' color='#00FF00'", Some(GREEN)),
+            ("color='red'", None),
+            ("color='#FF00'", None),
+            ("color='#FF0000", None),
+            ("color", None),
+            ("color=", None),
+            ("size='20'", None),
+            ("", None),
+        ];
+        for (attributes, expected) in cases {
+            assert_eq!(parse_color_attribute(attributes), expected, "{attributes}");
+        }
+    }
+
+    #[test]
+    fn unclosed_tags() {
+        // The style lasts to the end.
+        let text = RichText::parse("a<b>b<font color='#FF0000'>c");
+        assert_eq!(
+            text.spans,
+            [
+                span("a", None, false),
+                span("b", None, true),
+                span("c", Some(RED), true),
+            ]
+        );
+
+        // Extra closing tags are ignored.
+        let text = RichText::parse("a</b></font>b");
+        assert_eq!(text.spans, [span("ab", None, false)]);
+
+        // `<` without `>` is text.
+        let text = RichText::parse("1 <b>2</b> < 3");
+        assert_eq!(text.to_plain_string(), "1 2 < 3");
+    }
+
+    #[test]
+    fn blank() {
+        assert!(RichText::parse("").is_blank());
+        assert!(RichText::parse("<font color='#FF0000'> </font><br>").is_blank());
+        assert!(!RichText::parse("<b>x</b>").is_blank());
+    }
+
+    /// One unit per char.
+    fn measure(text: &str) -> f32 {
+        text.chars().count() as f32
+    }
+
+    fn positions<'a>(runs: &[Run<'a>]) -> Vec<(&'a str, [f32; 2])> {
+        runs.iter().map(|run| (run.text, run.pos)).collect()
+    }
+
+    #[test]
+    fn layout_wraps_words() {
+        let spans = [span("one two  three", None, false)];
+
+        let (runs, size) = layout(&spans, Some(7.0), 10.0, measure);
+        assert_eq!(
+            positions(&runs),
+            [
+                ("one", [0.0, 0.0]),
+                ("two", [4.0, 0.0]),
+                ("three", [0.0, 10.0])
+            ]
+        );
+        assert_eq!(size, [7.0, 20.0]);
+
+        let (runs, size) = layout(&spans, None, 10.0, measure);
+        assert_eq!(
+            positions(&runs),
+            [
+                ("one", [0.0, 0.0]),
+                ("two", [4.0, 0.0]),
+                ("three", [9.0, 0.0])
+            ]
+        );
+        assert_eq!(size, [14.0, 10.0]);
+
+        // A word longer than the width gets its own line.
+        let spans = [span("a abcdefgh b", None, false)];
+        let (runs, _) = layout(&spans, Some(4.0), 10.0, measure);
+        assert_eq!(
+            positions(&runs),
+            [
+                ("a", [0.0, 0.0]),
+                ("abcdefgh", [0.0, 10.0]),
+                ("b", [0.0, 20.0])
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_keeps_styles_across_spans() {
+        let text = RichText::parse("Deals <font color='#FF0000'>25</font>\n points");
+        let (runs, size) = layout(&text.spans, None, 10.0, measure);
+        assert_eq!(
+            runs,
+            [
+                Run {
+                    text: "Deals",
+                    pos: [0.0, 0.0],
+                    color: None,
+                    bold: false,
+                },
+                Run {
+                    text: "25",
+                    pos: [6.0, 0.0],
+                    color: Some(RED),
+                    bold: false,
+                },
+                // No leading spaces on a line.
+                Run {
+                    text: "points",
+                    pos: [0.0, 10.0],
+                    color: None,
+                    bold: false,
+                },
+            ]
+        );
+        assert_eq!(size, [8.0, 20.0]);
+
+        // `&nbsp;` doesn't break.
+        let spans = [span("a\u{a0}b c", None, false)];
+        let (runs, _) = layout(&spans, Some(3.0), 10.0, measure);
+        assert_eq!(
+            positions(&runs),
+            [("a\u{a0}b", [0.0, 0.0]), ("c", [0.0, 10.0])]
+        );
+    }
+}
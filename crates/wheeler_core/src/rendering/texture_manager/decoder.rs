@@ -0,0 +1,433 @@
+use std::path::Path;
+
+use resvg::tiny_skia;
+use snafu::{OptionExt as _, ResultExt as _};
+use windows::Win32::Graphics::Dxgi::Common::{
+    DXGI_FORMAT, DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_B8G8R8X8_UNORM, DXGI_FORMAT_R8G8B8A8_UNORM,
+};
+
+/// Supported icon file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImageFormat {
+    Svg,
+    Png,
+    Dds,
+}
+
+impl ImageFormat {
+    /// Select format by file extension.(case insensitive)
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?;
+        if ext.eq_ignore_ascii_case("svg") {
+            Some(Self::Svg)
+        } else if ext.eq_ignore_ascii_case("png") {
+            Some(Self::Png)
+        } else if ext.eq_ignore_ascii_case("dds") {
+            Some(Self::Dds)
+        } else {
+            None
+        }
+    }
+
+    /// Does the header of `data` look like this format?
+    pub(crate) fn matches_magic(self, data: &[u8]) -> bool {
+        match self {
+            Self::Svg => {
+                // svg is xml text, so only check that it starts with a tag. (e.g. `<?xml`, `<svg`, `<!--`)
+                let text = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+                text.trim_ascii_start().starts_with(b"<")
+            }
+            Self::Png => data.starts_with(b"\x89PNG\r\n\x1A\n"),
+            Self::Dds => data.starts_with(b"DDS "),
+        }
+    }
+}
+
+/// Decoded pixels that can be passed to `ID3D11Device::CreateTexture2D` as is.
+#[derive(Debug)]
+pub(crate) struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub format: DXGI_FORMAT,
+    /// Bytes per row. (For block compressed formats, bytes per row of 4x4 blocks)
+    pub row_pitch: u32,
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub(crate) enum DecodeError {
+    /// The file header does not match `{format:?}` format.
+    MismatchedMagic { format: ImageFormat },
+
+    /// Failed to parse svg: {source}
+    FailedToParseSvg { source: usvg::Error },
+
+    /// Invalid svg size. width: {width}, height: {height}
+    InvalidSvgSize { width: f32, height: f32 },
+
+    /// Failed to decode png: {source}
+    FailedToDecodePng { source: png::DecodingError },
+
+    /// Unsupported png color type: {color_type:?}
+    UnsupportedPngColorType { color_type: png::ColorType },
+
+    /// Failed to parse dds: {source}
+    FailedToParseDds { source: ddsfile::Error },
+
+    /// Unsupported dds format. Expected uncompressed (A8R8G8B8, A8B8G8R8, X8R8G8B8) or BC1-BC7.
+    UnsupportedDdsFormat,
+
+    /// Block compressed dds size must be a multiple of 4. width: {width}, height: {height}
+    InvalidBlockCompressedSize { width: u32, height: u32 },
+}
+
+/// Decode `data` as `format`.
+///
+/// # Errors
+/// If the magic bytes do not match `format` or the data is broken.
+pub(crate) fn decode(format: ImageFormat, data: &[u8]) -> Result<DecodedImage, DecodeError> {
+    if !format.matches_magic(data) {
+        return Err(DecodeError::MismatchedMagic { format });
+    }
+
+    match format {
+        ImageFormat::Svg => decode_svg(data),
+        ImageFormat::Png => decode_png(data),
+        ImageFormat::Dds => decode_dds(data),
+    }
+}
+
+fn decode_svg(data: &[u8]) -> Result<DecodedImage, DecodeError> {
+    // https://github.com/linebender/resvg/blob/main/crates/usvg/tests/parser.rs#L193
+    let tree =
+        usvg::Tree::from_data(data, &usvg::Options::default()).context(FailedToParseSvgSnafu)?;
+    let size = tree.size();
+
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width().ceil() as u32, size.height().ceil() as u32).context(
+            InvalidSvgSizeSnafu {
+                width: size.width(),
+                height: size.height(),
+            },
+        )?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    // tiny-skia holds premultiplied alpha, but imgui expects straight alpha.
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    Ok(DecodedImage {
+        width: pixmap.width(),
+        height: pixmap.height(),
+        format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        row_pitch: pixmap.width() * 4,
+        pixels,
+    })
+}
+
+fn decode_png(data: &[u8]) -> Result<DecodedImage, DecodeError> {
+    let mut decoder = png::Decoder::new(data);
+    // Expand palette/low bit depth and strip 16bit -> 8bit channels.
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().context(FailedToDecodePngSnafu)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .context(FailedToDecodePngSnafu)?;
+    buf.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|ga| [ga[0], ga[0], ga[0], ga[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, u8::MAX]).collect(),
+        color_type @ png::ColorType::Indexed => {
+            return Err(DecodeError::UnsupportedPngColorType { color_type });
+        }
+    };
+
+    Ok(DecodedImage {
+        width: info.width,
+        height: info.height,
+        format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        row_pitch: info.width * 4,
+        pixels,
+    })
+}
+
+/// Only the top mip level is used.
+fn decode_dds(data: &[u8]) -> Result<DecodedImage, DecodeError> {
+    use ddsfile::{D3DFormat, DxgiFormat};
+
+    let dds = ddsfile::Dds::read(data).context(FailedToParseDdsSnafu)?;
+
+    let (width, height) = (dds.get_width(), dds.get_height());
+    let format = match (dds.get_dxgi_format(), dds.get_d3d_format()) {
+        (Some(format), _) => match format {
+            DxgiFormat::R8G8B8A8_UNorm
+            | DxgiFormat::R8G8B8A8_UNorm_sRGB
+            | DxgiFormat::B8G8R8A8_UNorm
+            | DxgiFormat::B8G8R8A8_UNorm_sRGB
+            | DxgiFormat::B8G8R8X8_UNorm
+            | DxgiFormat::B8G8R8X8_UNorm_sRGB => DXGI_FORMAT(format as i32),
+            // `CreateTexture2D` rejects the top level of these not in whole 4x4 blocks.
+            DxgiFormat::BC1_UNorm
+            | DxgiFormat::BC1_UNorm_sRGB
+            | DxgiFormat::BC2_UNorm
+            | DxgiFormat::BC2_UNorm_sRGB
+            | DxgiFormat::BC3_UNorm
+            | DxgiFormat::BC3_UNorm_sRGB
+            | DxgiFormat::BC4_UNorm
+            | DxgiFormat::BC4_SNorm
+            | DxgiFormat::BC5_UNorm
+            | DxgiFormat::BC5_SNorm
+            | DxgiFormat::BC7_UNorm
+            | DxgiFormat::BC7_UNorm_sRGB => {
+                if width % 4 != 0 || height % 4 != 0 {
+                    return Err(DecodeError::InvalidBlockCompressedSize { width, height });
+                }
+                DXGI_FORMAT(format as i32)
+            }
+            _ => return Err(DecodeError::UnsupportedDdsFormat),
+        },
+        (None, Some(D3DFormat::A8B8G8R8)) => DXGI_FORMAT_R8G8B8A8_UNORM,
+        (None, Some(D3DFormat::A8R8G8B8)) => DXGI_FORMAT_B8G8R8A8_UNORM,
+        (None, Some(D3DFormat::X8R8G8B8)) => DXGI_FORMAT_B8G8R8X8_UNORM,
+        _ => return Err(DecodeError::UnsupportedDdsFormat),
+    };
+
+    let row_pitch = dds.get_pitch().context(UnsupportedDdsFormatSnafu)?;
+    let main_size = dds
+        .get_main_texture_size()
+        .context(UnsupportedDdsFormatSnafu)? as usize;
+    let pixels = dds
+        .get_data(0)
+        .context(FailedToParseDdsSnafu)?
+        .get(..main_size)
+        .context(UnsupportedDdsFormatSnafu)?
+        .to_vec();
+
+    Ok(DecodedImage {
+        width,
+        height,
+        format,
+        row_pitch,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ddsfile::{AlphaMode, D3D10ResourceDimension, Dds, DxgiFormat, NewDxgiParams};
+    use pretty_assertions::assert_eq;
+
+    const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1A\n";
+
+    fn dds_bytes(width: u32, height: u32, format: DxgiFormat) -> Vec<u8> {
+        let dds = Dds::new_dxgi(NewDxgiParams {
+            height,
+            width,
+            depth: None,
+            format,
+            mipmap_levels: None,
+            array_layers: None,
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: D3D10ResourceDimension::Texture2D,
+            alpha_mode: AlphaMode::Unknown,
+        })
+        .unwrap();
+        let mut data = Vec::new();
+        dds.write(&mut data).unwrap();
+        data
+    }
+
+    fn png_bytes(width: u32, height: u32, color_type: png::ColorType, pixels: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    #[test]
+    fn format_from_path() {
+        let cases = [
+            ("icon.svg", Some(ImageFormat::Svg)),
+            ("icon.PNG", Some(ImageFormat::Png)),
+            ("dir.dds/icon.Dds", Some(ImageFormat::Dds)),
+            ("icon.jpg", None),
+            ("icon.png.bak", None),
+            ("icon", None),
+            (".png", None),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(ImageFormat::from_path(Path::new(path)), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn magic() {
+        let cases: [(ImageFormat, &[u8], bool); 10] = [
+            (ImageFormat::Svg, b"<svg/>", true),
+            (ImageFormat::Svg, b"\xEF\xBB\xBF \r\n<?xml?>", true),
+            (ImageFormat::Svg, b"svg", false),
+            (ImageFormat::Svg, b"", false),
+            (ImageFormat::Png, PNG_MAGIC, true),
+            (ImageFormat::Png, &PNG_MAGIC[..4], false),
+            (ImageFormat::Png, b"DDS ", false),
+            (ImageFormat::Dds, b"DDS \x7C", true),
+            (ImageFormat::Dds, b"DDS", false),
+            (ImageFormat::Dds, PNG_MAGIC, false),
+        ];
+        for (format, data, expected) in cases {
+            assert_eq!(format.matches_magic(data), expected, "{format:?} {data:?}");
+        }
+    }
+
+    #[test]
+    fn extension_mismatching_magic() {
+        let png = png_bytes(1, 1, png::ColorType::Rgba, &[0; 4]);
+        let dds = dds_bytes(4, 4, DxgiFormat::BC1_UNorm);
+
+        let cases = [
+            (ImageFormat::Dds, &png),
+            (ImageFormat::Svg, &png),
+            (ImageFormat::Png, &dds),
+            (ImageFormat::Svg, &dds),
+        ];
+        for (format, data) in cases {
+            let result = decode(format, data);
+            assert!(
+                matches!(result, Err(DecodeError::MismatchedMagic { format: f }) if f == format),
+                "{format:?}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_headers() {
+        let png = png_bytes(2, 2, png::ColorType::Rgba, &[0; 16]);
+        let dds = dds_bytes(4, 4, DxgiFormat::BC1_UNorm);
+
+        for len in [0, 4] {
+            let result = decode(ImageFormat::Png, &png[..len]);
+            assert!(
+                matches!(result, Err(DecodeError::MismatchedMagic { .. })),
+                "{len}: {result:?}"
+            );
+        }
+        for len in [PNG_MAGIC.len(), 20, png.len() - 20] {
+            let result = decode(ImageFormat::Png, &png[..len]);
+            assert!(
+                matches!(result, Err(DecodeError::FailedToDecodePng { .. })),
+                "{len}: {result:?}"
+            );
+        }
+
+        let result = decode(ImageFormat::Dds, &dds[..3]);
+        assert!(
+            matches!(result, Err(DecodeError::MismatchedMagic { .. })),
+            "{result:?}"
+        );
+        for len in [4, 10, 100] {
+            let result = decode(ImageFormat::Dds, &dds[..len]);
+            assert!(
+                matches!(result, Err(DecodeError::FailedToParseDds { .. })),
+                "{len}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn block_compressed_size() {
+        let image = decode(ImageFormat::Dds, &dds_bytes(8, 4, DxgiFormat::BC1_UNorm)).unwrap();
+        assert_eq!((image.width, image.height), (8, 4));
+        assert_eq!(image.format, DXGI_FORMAT(DxgiFormat::BC1_UNorm as i32));
+        // 2 blocks of 8 bytes
+        assert_eq!((image.row_pitch, image.pixels.len()), (16, 16));
+
+        for (width, height, format) in [
+            (6, 4, DxgiFormat::BC1_UNorm),
+            (4, 2, DxgiFormat::BC3_UNorm),
+            (5, 5, DxgiFormat::BC7_UNorm),
+        ] {
+            let result = decode(ImageFormat::Dds, &dds_bytes(width, height, format));
+            assert!(
+                matches!(
+                    result,
+                    Err(DecodeError::InvalidBlockCompressedSize { width: w, height: h })
+                        if (w, h) == (width, height)
+                ),
+                "{width}x{height} {format:?}: {result:?}"
+            );
+        }
+
+        // Uncompressed ones can be any size.
+        let image = decode(
+            ImageFormat::Dds,
+            &dds_bytes(6, 3, DxgiFormat::B8G8R8A8_UNorm),
+        )
+        .unwrap();
+        assert_eq!(image.format, DXGI_FORMAT_B8G8R8A8_UNORM);
+        assert_eq!((image.row_pitch, image.pixels.len()), (24, 72));
+    }
+
+    #[test]
+    fn png_to_rgba() {
+        let cases: [(png::ColorType, &[u8], [u8; 8]); 3] = [
+            (
+                png::ColorType::Rgb,
+                &[1, 2, 3, 4, 5, 6],
+                [1, 2, 3, 255, 4, 5, 6, 255],
+            ),
+            (
+                png::ColorType::GrayscaleAlpha,
+                &[7, 8, 9, 10],
+                [7, 7, 7, 8, 9, 9, 9, 10],
+            ),
+            (
+                png::ColorType::Grayscale,
+                &[11, 12],
+                [11, 11, 11, 255, 12, 12, 12, 255],
+            ),
+        ];
+        for (color_type, pixels, expected) in cases {
+            let image = decode(ImageFormat::Png, &png_bytes(2, 1, color_type, pixels)).unwrap();
+            assert_eq!((image.width, image.height, image.row_pitch), (2, 1, 8));
+            assert_eq!(image.format, DXGI_FORMAT_R8G8B8A8_UNORM);
+            assert_eq!(image.pixels, expected, "{color_type:?}");
+        }
+    }
+
+    #[test]
+    fn svg_size() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="3" height="2"><rect width="3" height="2" fill="red"/></svg>"#;
+        let image = decode(ImageFormat::Svg, svg).unwrap();
+        assert_eq!((image.width, image.height, image.row_pitch), (3, 2, 12));
+        assert_eq!(image.pixels[..4], [0xFF, 0, 0, 0xFF]);
+
+        let result = decode(ImageFormat::Svg, b"<svg");
+        assert!(
+            matches!(result, Err(DecodeError::FailedToParseSvg { .. })),
+            "{result:?}"
+        );
+    }
+}
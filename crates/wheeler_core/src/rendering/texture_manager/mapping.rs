@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+
+use commonlibsse_ng::re::FormTypes::FormType;
+
+use crate::rendering::color::Color;
+
+/// Optional mapping file in each custom icon directory.
+pub(crate) const MAPPING_FILE: &str = "mapping.toml";
+
+/// `icons_custom/mapping.toml`
+///
+/// ```toml
+/// [[icon]]
+/// form = "Skyrim.esm|0x00012EB7" # `<plugin>|<local form id>`
+/// file = "iron_sword.svg"        # Relative to the mapping file
+/// tint = "#C0C0C0"
+///
+/// [[icon]]
+/// keyword = "WeapTypeSword"
+/// file = "sword.png"
+///
+/// [[icon]]
+/// editor_id = "IronSword"
+/// file = "iron_sword.dds"
+///
+/// [[icon]]
+/// form_type = "Scroll"
+/// file = "scroll.svg"
+/// ```
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct IconMapping {
+    #[serde(rename = "icon")]
+    pub icons: Vec<IconMappingEntry>,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+#[serde(try_from = "RawIconMappingEntry")]
+pub(crate) struct IconMappingEntry {
+    pub target: MappingTarget,
+    pub file: PathBuf,
+    pub tint: Option<Color>,
+}
+
+/// `[[icon]]` as written.
+///
+/// `#[serde(flatten)]` of `MappingTarget` silently ignores unknown keys and all but one target key,
+/// so the target is picked by hand.
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawIconMappingEntry {
+    form: Option<FormRef>,
+    keyword: Option<String>,
+    editor_id: Option<String>,
+    form_type: Option<String>,
+    file: PathBuf,
+    tint: Option<Color>,
+}
+
+impl TryFrom<RawIconMappingEntry> for IconMappingEntry {
+    type Error = String;
+
+    fn try_from(raw: RawIconMappingEntry) -> Result<Self, Self::Error> {
+        let mut targets = [
+            raw.form.map(MappingTarget::Form),
+            raw.keyword.map(MappingTarget::Keyword),
+            raw.editor_id.map(MappingTarget::EditorId),
+            raw.form_type.map(MappingTarget::FormType),
+        ]
+        .into_iter()
+        .flatten();
+
+        let target = targets.next().ok_or_else(|| {
+            format!(
+                "Missing icon target of `{}`. Expected one of `form`, `keyword`, `editor_id`, `form_type`",
+                raw.file.display()
+            )
+        })?;
+        if targets.next().is_some() {
+            return Err(format!(
+                "Ambiguous icon target of `{}`. Expected only one of `form`, `keyword`, `editor_id`, `form_type`",
+                raw.file.display()
+            ));
+        }
+
+        Ok(Self {
+            target,
+            file: raw.file,
+            tint: raw.tint,
+        })
+    }
+}
+
+/// What the icon is used for. Exactly one key per `[[icon]]`.
+#[derive(Debug, PartialEq)]
+pub(crate) enum MappingTarget {
+    Form(FormRef),
+    Keyword(String),
+    EditorId(String),
+    FormType(String),
+}
+
+/// `<plugin>|<local form id>` e.g. `Skyrim.esm|0x00012EB7`
+///
+/// Unlike the `FID_<plugin>_0x<id>` file name, the plugin name may contain `_`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct FormRef {
+    pub plugin: String,
+    pub local_id: u32,
+}
+
+/// The upper byte of a form id is the load order index (`FE` + ESL index for light plugins).
+const MAX_LOCAL_FORM_ID: u32 = 0x00FF_FFFF;
+
+impl TryFrom<String> for FormRef {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("Invalid form reference `{value}`. Expected `<plugin>|0x<id>`");
+
+        let (plugin, id) = value.rsplit_once('|').ok_or_else(invalid)?;
+        let id = id.trim();
+        let id = id
+            .strip_prefix("0x")
+            .or_else(|| id.strip_prefix("0X"))
+            .unwrap_or(id);
+        let local_id = u32::from_str_radix(id, 16).map_err(|_| invalid())?;
+        if local_id > MAX_LOCAL_FORM_ID {
+            return Err(format!(
+                "Invalid form reference `{value}`. The id must be local to the plugin (at most 0x{MAX_LOCAL_FORM_ID:06X}), without the load order index"
+            ));
+        }
+
+        Ok(Self {
+            plugin: plugin.trim().to_string(),
+            local_id,
+        })
+    }
+}
+
+/// Read `<dir>/mapping.toml`. Returns `None` if there is no mapping file.
+pub(crate) fn read_mapping(dir: &Path) -> Option<IconMapping> {
+    let path = dir.join(MAPPING_FILE);
+    let text = std::fs::read_to_string(&path).ok()?;
+
+    match toml::from_str(&text) {
+        Ok(mapping) => Some(mapping),
+        Err(err) => {
+            tracing::error!("Failed to parse icon mapping. path: {path:?}, error: {err}");
+            None
+        }
+    }
+}
+
+/// Parse the `form_type` value of `mapping.toml`. Only forms that can be put on the wheel are supported.
+pub(crate) fn parse_form_type(name: &str) -> Option<FormType> {
+    Some(match name {
+        "Weapon" => FormType::Weapon,
+        "Armor" => FormType::Armor,
+        "Ammo" => FormType::Ammo,
+        "Light" => FormType::Light,
+        "AlchemyItem" | "Potion" => FormType::AlchemyItem,
+        "Ingredient" => FormType::Ingredient,
+        "Scroll" => FormType::Scroll,
+        "Spell" => FormType::Spell,
+        "Shout" => FormType::Shout,
+        "Book" => FormType::Book,
+        "Misc" => FormType::Misc,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn form_ref(value: &str) -> Result<FormRef, String> {
+        FormRef::try_from(value.to_string())
+    }
+
+    fn parse(text: &str) -> Result<IconMapping, String> {
+        toml::from_str(text).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn parse_form_ref() {
+        let expected = FormRef {
+            plugin: "Skyrim.esm".to_string(),
+            local_id: 0x12EB7,
+        };
+        assert_eq!(form_ref("Skyrim.esm|0x00012EB7"), Ok(expected.clone()));
+        assert_eq!(form_ref(" Skyrim.esm | 0X12eb7 "), Ok(expected.clone()));
+        assert_eq!(form_ref("Skyrim.esm|12EB7"), Ok(expected));
+
+        // `_` and `|` are allowed in the plugin name, the id is after the last `|`.
+        assert_eq!(
+            form_ref("My_Mod|Extra.esp|0x800"),
+            Ok(FormRef {
+                plugin: "My_Mod|Extra.esp".to_string(),
+                local_id: 0x800,
+            })
+        );
+        assert_eq!(
+            form_ref("Light.esl|0x00FFFFFF").map(|form| form.local_id),
+            Ok(MAX_LOCAL_FORM_ID)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_form_ref() {
+        for value in [
+            "Skyrim.esm|0xZZ12",
+            "Skyrim.esm|",
+            "Skyrim.esm|0x",
+            "Skyrim.esm 0x00012EB7",
+            "Skyrim.esm|0x1_0000_0000",
+            // Runtime ids of a regular and a light plugin.
+            "Dawnguard.esm|0x02000800",
+            "ccBGSSSE001-Fish.esm|0xFE000801",
+        ] {
+            assert!(form_ref(value).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn deserialize_mapping() {
+        let mapping = parse(
+            r##"
+            [[icon]]
+            form = "Skyrim.esm|0x00012EB7"
+            file = "iron_sword.svg"
+            tint = "#C0C0C0"
+
+            [[icon]]
+            keyword = "WeapTypeSword"
+            file = "sword.png"
+
+            [[icon]]
+            editor_id = "IronSword"
+            file = "iron_sword.dds"
+
+            [[icon]]
+            form_type = "Scroll"
+            file = "scroll.svg"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            mapping.icons,
+            [
+                IconMappingEntry {
+                    target: MappingTarget::Form(FormRef {
+                        plugin: "Skyrim.esm".to_string(),
+                        local_id: 0x12EB7,
+                    }),
+                    file: PathBuf::from("iron_sword.svg"),
+                    tint: Some(Color::rgb(0xC0, 0xC0, 0xC0)),
+                },
+                IconMappingEntry {
+                    target: MappingTarget::Keyword("WeapTypeSword".to_string()),
+                    file: PathBuf::from("sword.png"),
+                    tint: None,
+                },
+                IconMappingEntry {
+                    target: MappingTarget::EditorId("IronSword".to_string()),
+                    file: PathBuf::from("iron_sword.dds"),
+                    tint: None,
+                },
+                IconMappingEntry {
+                    target: MappingTarget::FormType("Scroll".to_string()),
+                    file: PathBuf::from("scroll.svg"),
+                    tint: None,
+                },
+            ]
+        );
+        assert!(parse("").unwrap().icons.is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_mapping() {
+        let cases = [
+            // Bad form reference
+            "form = \"Skyrim.esm|0xNOPE\"\nfile = \"a.svg\"",
+            "form = \"Skyrim.esm\"\nfile = \"a.svg\"",
+            "form = \"Skyrim.esm|0xFE000801\"\nfile = \"a.svg\"",
+            // No target
+            "file = \"a.svg\"",
+            // Ambiguous target
+            "keyword = \"WeapTypeSword\"\neditor_id = \"IronSword\"\nfile = \"a.svg\"",
+            // Unknown keys
+            "form_id = \"Skyrim.esm|0x00012EB7\"\nfile = \"a.svg\"",
+            "keyword = \"WeapTypeSword\"\nfile = \"a.svg\"\ntnit = \"#FFFFFF\"",
+            // Missing file
+            "keyword = \"WeapTypeSword\"",
+        ];
+        for case in cases {
+            let text = format!("[[icon]]\n{case}");
+            assert!(parse(&text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn parse_wheel_form_types() {
+        assert_eq!(parse_form_type("Potion"), Some(FormType::AlchemyItem));
+        assert_eq!(parse_form_type("AlchemyItem"), Some(FormType::AlchemyItem));
+        assert_eq!(parse_form_type("Scroll"), Some(FormType::Scroll));
+        assert_eq!(parse_form_type("scroll"), None);
+        assert_eq!(parse_form_type("Npc"), None);
+    }
+}
@@ -0,0 +1,649 @@
+mod decoder;
+mod mapping;
+mod theme;
+mod tint;
+
+use core::{ptr::NonNull, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock},
+};
+
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng::{re::BSCoreTypes::FormID, rel::relocation::RelocationError};
+use commonlibsse_ng_re_ext::re::{
+    BSRenderManager::{BSRenderManager, RUNTIME_DATA},
+    TESDataHandler::TESDataHandler,
+};
+use dashmap::DashMap;
+use snafu::{OptionExt as _, ResultExt as _};
+use windows::Win32::Graphics::{
+    Direct3D::D3D11_SRV_DIMENSION_TEXTURE2D,
+    Direct3D11::{
+        D3D11_BIND_SHADER_RESOURCE, D3D11_SHADER_RESOURCE_VIEW_DESC,
+        D3D11_SHADER_RESOURCE_VIEW_DESC_0, D3D11_SUBRESOURCE_DATA, D3D11_TEX2D_SRV,
+        D3D11_TEXTURE2D_DESC, D3D11_USAGE_DEFAULT, ID3D11ShaderResourceView,
+    },
+    Dxgi::Common::DXGI_SAMPLE_DESC,
+};
+
+use self::decoder::{DecodeError, DecodedImage, ImageFormat};
+use self::mapping::{FormRef, IconMapping, IconMappingEntry, MappingTarget};
+use super::color::Color;
+use super::render_manager::DEVICE;
+
+pub use self::theme::DEFAULT_THEME;
+pub use self::tint::{Element, IconState};
+
+const ICON_DIRECTORY: &str = "Data/SKSE/Plugins/Wheeler/resources/icons";
+const ICON_CUSTOM_DIRECTORY: &str = "Data/SKSE/Plugins/Wheeler/resources/icons_custom";
+
+#[derive(Debug, snafu::Snafu)]
+enum TextureError {
+    /// Not found `BSRenderManager. AddressLibrary error.`
+    NotFoundRenderManager,
+    /// Not found runtime fields of `BSRenderManager`. AddressLibrary error.: {source}`
+    FailedToGetRuntimeData { source: RelocationError },
+
+    /// Not found image. path: {path:?}, error: {source}
+    NotFoundImage {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// Unsupported image extension. Expected `svg`, `png` or `dds`. path: {path:?}
+    UnsupportedImageExtension { path: PathBuf },
+
+    /// Failed to decode image. path: {path:?}, error: {source}
+    FailedToDecodeImage { path: PathBuf, source: DecodeError },
+
+    /// Failed to create 2D Texture. path: {path:?}, error: {source}
+    FailedToCreate2DTexture {
+        path: PathBuf,
+        source: windows::core::Error,
+    },
+
+    /// Failed to create shader resource view. path: {path:?}, error: {source}
+    FailedToCreateShaderResourceView {
+        path: PathBuf,
+        source: windows::core::Error,
+    },
+}
+
+pub struct Texture;
+
+impl Texture {
+    /// Load the icons of the theme selected by config.
+    ///
+    /// Themes are loaded from the end of the fallback chain, so that icons of the active theme
+    /// overwrite the fallback ones and missing icons remain as the fallback ones.
+    pub fn init() {
+        let active_theme = crate::config::get().icon.theme.clone();
+        let chain = theme::resolve_theme_chain(&active_theme);
+        tracing::info!(
+            "Icon theme chain: {:?}",
+            chain
+                .iter()
+                .map(|theme| theme.display_name())
+                .collect::<Vec<_>>()
+        );
+
+        // The nearest theme having `[tint]` decides the palette.
+        tint::set_palette(
+            chain
+                .iter()
+                .find_map(|theme| theme.manifest.tint.clone())
+                .unwrap_or_default(),
+        );
+        TINTED_ICONS.clear();
+
+        for theme in chain.iter().rev() {
+            Self::load_custom_icon_images(theme.custom_icon_dir());
+            Self::load_images(theme.icon_dir());
+        }
+    }
+
+    /// Return width & height
+    ///
+    /// Monochrome svg is rasterized with the normal tint color, and keeps its source for recoloring.
+    fn load_texture_from_file(file_name: impl AsRef<Path>) -> Result<Image, TextureError> {
+        let path = file_name.as_ref();
+        let format =
+            ImageFormat::from_path(path).with_context(|| UnsupportedImageExtensionSnafu {
+                path: path.to_path_buf(),
+            })?;
+        let image_data = std::fs::read(path).with_context(|_| NotFoundImageSnafu {
+            path: path.to_path_buf(),
+        })?;
+
+        let palette = tint::palette();
+        let recolor_source = match format {
+            ImageFormat::Svg => core::str::from_utf8(&image_data)
+                .ok()
+                .filter(|svg| tint::is_recolorable(svg, palette.marker))
+                .map(|svg| {
+                    Arc::new(RecolorSource {
+                        path: path.to_path_buf(),
+                        svg: svg.to_string(),
+                    })
+                }),
+            _ => None,
+        };
+
+        let decoded = match &recolor_source {
+            Some(source) => {
+                let svg = tint::recolor_svg(&source.svg, palette.marker, palette.normal);
+                decoder::decode(format, svg.as_bytes())
+            }
+            None => decoder::decode(format, &image_data),
+        }
+        .with_context(|_| FailedToDecodeImageSnafu {
+            path: path.to_path_buf(),
+        })?;
+
+        let mut image = Self::create_texture(path, &decoded)?;
+        image.recolor = recolor_source;
+        Ok(image)
+    }
+
+    /// Upload decoded pixels to GPU.
+    ///
+    /// `path` is used only for error messages.
+    fn create_texture(path: &Path, image: &DecodedImage) -> Result<Image, TextureError> {
+        let render_manager =
+            BSRenderManager::get_singleton().ok_or(TextureError::NotFoundRenderManager)?;
+
+        let RUNTIME_DATA { forwarder, .. } = render_manager
+            .get_runtime_data()
+            .with_context(|_| FailedToGetRuntimeDataSnafu)?;
+
+        let desc = D3D11_TEXTURE2D_DESC {
+            Width: image.width,
+            Height: image.height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: image.format,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D11_USAGE_DEFAULT,
+            BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+
+        let sub_resource = D3D11_SUBRESOURCE_DATA {
+            pSysMem: image.pixels.as_ptr().cast(),
+            SysMemPitch: image.row_pitch,
+            SysMemSlicePitch: 0,
+        };
+
+        let mut p_texture = None;
+        let device = DEVICE.get().ok_or(TextureError::NotFoundRenderManager)?;
+        unsafe { device.CreateTexture2D(&desc, Some(&sub_resource), Some(&mut p_texture)) }
+            .with_context(|_| FailedToCreate2DTextureSnafu {
+                path: path.to_path_buf(),
+            })?;
+
+        let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
+            Format: desc.Format,
+            ViewDimension: D3D11_SRV_DIMENSION_TEXTURE2D,
+            Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
+                Texture2D: D3D11_TEX2D_SRV {
+                    MostDetailedMip: 0,
+                    MipLevels: desc.MipLevels,
+                },
+            },
+        };
+
+        let mut out_srv = None;
+        unsafe {
+            let forwarder = forwarder
+                .as_ref()
+                .ok_or(TextureError::NotFoundRenderManager)?;
+
+            if let Some(p_texture) = p_texture {
+                forwarder
+                    .CreateShaderResourceView(&p_texture, Some(&srv_desc), Some(&mut out_srv))
+                    .with_context(|_| FailedToCreateShaderResourceViewSnafu {
+                        path: path.to_path_buf(),
+                    })?;
+            }
+        }
+
+        Ok(Image {
+            texture: out_srv,
+            width: image.width as i32,   // FIXME: valid cast?
+            height: image.height as i32, // FIXME: valid cast?
+            tint: None,
+            recolor: None,
+        })
+    }
+
+    /// Load icons directly under `file_path`. (Sub directories are other themes.)
+    fn load_images(file_path: impl AsRef<Path>) {
+        let walk_dir = jwalk::WalkDir::new(file_path)
+            .max_depth(1)
+            .into_iter()
+            .filter_map(Result::ok);
+
+        for entry in walk_dir {
+            let path = entry.path();
+            if !is_image_file(&path) {
+                continue;
+            }
+
+            let Some(file_stem) = path.file_stem() else {
+                continue;
+            };
+            let index = match IconImageType::from_str(&file_stem.to_string_lossy()) {
+                Ok(index) => index,
+                Err(err) => {
+                    tracing::error!("{err}");
+                    continue;
+                }
+            };
+
+            match Self::load_texture_from_file(&path) {
+                Ok(image) => ICON_STRUCT.insert(index, image),
+                Err(err) => {
+                    tracing::error!("{err}");
+                    continue;
+                }
+            };
+        }
+    }
+
+    /// Load `FID_`/`KWD_` named icons, then `mapping.toml` entries. (mapping takes priority)
+    fn load_custom_icon_images(file_path: impl AsRef<Path>) {
+        let file_path = file_path.as_ref();
+        let Some(handler) = TESDataHandler::get_singleton() else {
+            tracing::error!("Failed to get `TESDataHandler`");
+            return;
+        };
+
+        for entry in jwalk::WalkDir::new(file_path)
+            .into_iter()
+            .filter_map(Result::ok)
+        {
+            let path = entry.path();
+
+            if !is_image_file(&path) {
+                continue;
+            }
+
+            let file_stem = path.file_stem().unwrap().to_string_lossy();
+            let form_id = parse_form_id_from_filename(&file_stem);
+            let keyword = match form_id {
+                Some(_) => None,
+                None => parse_keyword_from_filename(&file_stem),
+            };
+            if form_id.is_none() && keyword.is_none() {
+                continue; // e.g. files referenced by `mapping.toml`
+            }
+
+            let image = match Self::load_texture_from_file(path.as_path()) {
+                Ok(img) => img,
+                Err(err) => {
+                    tracing::error!("{err}");
+                    continue;
+                }
+            };
+
+            if let Some((plugin, form_id)) = form_id {
+                if let Some(form) = handler
+                    .lookup_form(FormID::new(form_id), plugin)
+                    .map(|form| unsafe { form.as_ref() })
+                {
+                    ICON_STRUCT_FORM_ID.insert(form.formID, image);
+                }
+            } else if let Some(keyword) = keyword {
+                ICON_STRUCT_KEYWORD.insert(keyword, image);
+            }
+        }
+
+        if let Some(mapping) = mapping::read_mapping(file_path) {
+            Self::load_icon_mapping(handler, file_path, mapping);
+        }
+    }
+
+    fn load_icon_mapping(handler: &TESDataHandler, dir: &Path, mapping: IconMapping) {
+        // The same file may be mapped to many targets. Share the texture.
+        let mut loaded: HashMap<PathBuf, Image> = HashMap::new();
+
+        for IconMappingEntry { target, file, tint } in mapping.icons {
+            let path = dir.join(file);
+            let image = match loaded.get(&path) {
+                Some(image) => image.clone(),
+                None => match Self::load_texture_from_file(&path) {
+                    Ok(image) => {
+                        loaded.insert(path, image.clone());
+                        image
+                    }
+                    Err(err) => {
+                        tracing::error!("{err}");
+                        continue;
+                    }
+                },
+            };
+            let image = Image { tint, ..image };
+
+            match target {
+                MappingTarget::Form(FormRef { plugin, local_id }) => {
+                    match handler.lookup_form(FormID::new(local_id), &plugin) {
+                        Some(form) => {
+                            ICON_STRUCT_FORM_ID.insert(unsafe { form.as_ref() }.formID, image);
+                        }
+                        None => tracing::error!(
+                            "Icon mapping: Not found form `{plugin}|0x{local_id:08X}`"
+                        ),
+                    }
+                }
+                MappingTarget::Keyword(keyword) => {
+                    ICON_STRUCT_KEYWORD.insert(keyword, image);
+                }
+                MappingTarget::EditorId(editor_id) => {
+                    match TESForm::lookup_by_editor_id(&editor_id) {
+                        Some(form) => {
+                            ICON_STRUCT_FORM_ID.insert(unsafe { form.as_ref() }.formID, image);
+                        }
+                        None => tracing::error!("Icon mapping: Not found editor id `{editor_id}`"),
+                    }
+                }
+                MappingTarget::FormType(name) => match mapping::parse_form_type(&name) {
+                    Some(form_type) => {
+                        ICON_STRUCT_FORM_TYPE.insert(form_type as u8, image);
+                    }
+                    None => tracing::error!("Icon mapping: Unsupported form type `{name}`"),
+                },
+            }
+        }
+    }
+}
+
+/// Is it `svg`, `png` or `dds`?
+fn is_image_file(path: &std::path::Path) -> bool {
+    ImageFormat::from_path(path).is_some()
+}
+
+/// Parse `FID_<plugin>_0x<id>` file stem.
+fn parse_form_id_from_filename(file_stem: &str) -> Option<(&str, u32)> {
+    let rest = file_stem.strip_prefix("FID_")?;
+    let (plugin, hex_id) = rest.rsplit_once("_0x").or_else(|| rest.rsplit_once("_0X"))?;
+    let form_id = u32::from_str_radix(hex_id, 16).ok()?;
+    Some((plugin, form_id))
+}
+
+/// Parse `KWD_<keyword>` file stem.
+fn parse_keyword_from_filename(file_stem: &str) -> Option<String> {
+    file_stem.strip_prefix("KWD_").map(str::to_string)
+}
+
+static ICON_STRUCT: LazyLock<DashMap<IconImageType, Image>> = LazyLock::new(DashMap::new);
+static ICON_STRUCT_FORM_ID: LazyLock<DashMap<FormID, Image>> = LazyLock::new(DashMap::new);
+static ICON_STRUCT_KEYWORD: LazyLock<DashMap<String, Image>> = LazyLock::new(DashMap::new);
+/// key: `FormType as u8`
+static ICON_STRUCT_FORM_TYPE: LazyLock<DashMap<u8, Image>> = LazyLock::new(DashMap::new);
+
+/// Cloning is cheap. (Only increments the reference count of the texture)
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub texture: Option<ID3D11ShaderResourceView>,
+    pub width: i32,
+    pub height: i32,
+    /// Tint color from `mapping.toml`
+    pub tint: Option<Color>,
+    /// `Some` if this is a monochrome svg that can be recolored.
+    recolor: Option<Arc<RecolorSource>>,
+}
+
+#[derive(Debug)]
+struct RecolorSource {
+    path: PathBuf,
+    svg: String,
+}
+
+/// Recolored svg icons. key: (source path, color)
+static TINTED_ICONS: LazyLock<DashMap<(PathBuf, Color), Image>> = LazyLock::new(DashMap::new);
+
+/// Lookup order: form id(`FID_`, `form`, `editor_id`) -> `form_type` -> `image_type`
+pub fn get_icon_image(image_type: IconImageType, form: Option<NonNull<TESForm>>) -> Option<Image> {
+    if let Some(form) = form.map(|form| unsafe { form.as_ref() }) {
+        if let Some(image) = ICON_STRUCT_FORM_ID.get(&form.formID) {
+            return Some(image.clone());
+        }
+        if let Some(image) = ICON_STRUCT_FORM_TYPE.get(&(form.get_form_type() as u8)) {
+            return Some(image.clone());
+        }
+    }
+
+    ICON_STRUCT.get(&image_type).map(|image| image.clone())
+}
+
+/// Same as [`get_icon_image`], but monochrome svg icons are recolored by `state` and `element`.
+///
+/// - `element`: If `None`, it is guessed from `image_type`. (e.g. `DestructionFire` -> `Fire`)
+/// - Recolored textures are created on first use and cached.
+/// - Non-svg icons are returned as is. Use `Image::tint` to tint them when drawing.
+pub fn get_tinted_icon_image(
+    image_type: IconImageType,
+    form: Option<NonNull<TESForm>>,
+    state: IconState,
+    element: Option<Element>,
+) -> Option<Image> {
+    let image = get_icon_image(image_type, form)?;
+    let Some(source) = image.recolor.clone() else {
+        return Some(image);
+    };
+
+    let palette = tint::palette();
+    let accent = image.tint.or_else(|| {
+        element
+            .or_else(|| image_type.element())
+            .map(|element| palette.element(element))
+    });
+    let color = palette.resolve(state, accent);
+
+    let key = (source.path.clone(), color);
+    if let Some(tinted) = TINTED_ICONS.get(&key) {
+        return Some(tinted.clone());
+    }
+
+    let svg = tint::recolor_svg(&source.svg, palette.marker, color);
+    let tinted = decoder::decode(ImageFormat::Svg, svg.as_bytes())
+        .with_context(|_| FailedToDecodeImageSnafu {
+            path: source.path.clone(),
+        })
+        .and_then(|decoded| Texture::create_texture(&source.path, &decoded));
+
+    match tinted {
+        Ok(mut tinted) => {
+            tinted.recolor = Some(source);
+            TINTED_ICONS.insert(key, tinted.clone());
+            Some(tinted)
+        }
+        Err(err) => {
+            tracing::error!("{err}");
+            Some(image)
+        }
+    }
+}
+
+/// Get the icon of `KWD_<keyword>` or `keyword` in `mapping.toml`.
+pub fn get_icon_image_by_keyword(keyword: &str) -> Option<Image> {
+    ICON_STRUCT_KEYWORD.get(keyword).map(|image| image.clone())
+}
+
+#[derive(Debug)]
+enum ImageType {
+    Hud,
+    Round,
+    Key,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IconImageType {
+    PotionHealth,
+    PotionDefault,
+    SwordOneHanded,
+    AxeOneHanded,
+    Mace,
+    Dagger,
+    SwordTwoHanded,
+    AxeTwoHanded,
+    WarhammerTwoHanded,
+    Staff,
+    Bow,
+    Crossbow,
+    SpellDefault,
+    Destruction,
+    Shout,
+    Power,
+    Food,
+    Shield,
+    IconDefault,
+    DestructionFire,
+    DestructionFrost,
+    DestructionShock,
+    Restoration,
+    PoisonDefault,
+    ArmorHeavyShield,
+    ArmorLightShield,
+    ArmorLightChest,
+    ArmorHeavyChest,
+    ArmorLightArm,
+    ArmorHeavyArm,
+    ArmorLightFoot,
+    ArmorHeavyFoot,
+    ArmorLightHead,
+    ArmorHeavyHead,
+    ArmorClothingHead,
+    ArmorClothingChest,
+    ArmorClothingFoot,
+    ArmorClothingArm,
+    ArmorNecklace,
+    ArmorCirclet,
+    ArmorRing,
+    ArmorDefault,
+    Scroll,
+    Arrow,
+    HandToHand,
+    PotionStamina,
+    PotionMagicka,
+    PotionFireResist,
+    PotionShockResist,
+    PotionFrostResist,
+    PotionMagicResist,
+    Alteration,
+    Conjuration,
+    Illusion,
+    Torch,
+    Lantern,
+    Mask,
+    ArmorRating,
+    WeaponDamage,
+    SlotBackground,
+    SlotHighlightedBackground,
+    SlotActiveBackground,
+    WheelBackground,
+    WheelIndicatorActive,
+    WheelIndicatorInactive,
+}
+
+impl IconImageType {
+    /// Accent color group of the icon for recoloring.
+    pub const fn element(self) -> Option<Element> {
+        Some(match self {
+            Self::DestructionFire | Self::PotionFireResist => Element::Fire,
+            Self::DestructionFrost | Self::PotionFrostResist => Element::Frost,
+            Self::DestructionShock | Self::PotionShockResist => Element::Shock,
+            Self::Destruction => Element::Destruction,
+            Self::Restoration => Element::Restoration,
+            Self::Alteration => Element::Alteration,
+            Self::Conjuration => Element::Conjuration,
+            Self::Illusion => Element::Illusion,
+            _ => return None,
+        })
+    }
+}
+
+impl core::str::FromStr for IconImageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "potion_health" => Self::PotionHealth,
+            "potion_default" => Self::PotionDefault,
+            "sword_one_handed" => Self::SwordOneHanded,
+            "axe_one_handed" => Self::AxeOneHanded,
+            "mace" => Self::Mace,
+            "dagger" => Self::Dagger,
+            "sword_two_handed" => Self::SwordTwoHanded,
+            "axe_two_handed" => Self::AxeTwoHanded,
+            "warhammer_two_handed" => Self::WarhammerTwoHanded,
+            "staff" => Self::Staff,
+            "bow" => Self::Bow,
+            "crossbow" => Self::Crossbow,
+            "spell_default" => Self::SpellDefault,
+            "destruction" => Self::Destruction,
+            "shout" => Self::Shout,
+            "power" => Self::Power,
+            "food" => Self::Food,
+            "shield" => Self::Shield,
+            "icon_default" => Self::IconDefault,
+            "destruction_fire" => Self::DestructionFire,
+            "destruction_frost" => Self::DestructionFrost,
+            "destruction_shock" => Self::DestructionShock,
+            "restoration" => Self::Restoration,
+            "poison_default" => Self::PoisonDefault,
+            "armor_light_chest" => Self::ArmorLightChest,
+            "armor_heavy_chest" => Self::ArmorHeavyChest,
+            "armor_light_arm" => Self::ArmorLightArm,
+            "armor_heavy_arm" => Self::ArmorHeavyArm,
+            "armor_light_foot" => Self::ArmorLightFoot,
+            "armor_heavy_foot" => Self::ArmorHeavyFoot,
+            "armor_light_head" => Self::ArmorLightHead,
+            "armor_heavy_head" => Self::ArmorHeavyHead,
+            "armor_heavy_shield" => Self::ArmorHeavyShield,
+            "armor_light_shield" => Self::ArmorLightShield,
+            "armor_clothing_head" => Self::ArmorClothingHead,
+            "armor_clothing_chest" => Self::ArmorClothingChest,
+            "armor_clothing_arm" => Self::ArmorClothingArm,
+            "armor_clothing_foot" => Self::ArmorClothingFoot,
+            "armor_circlet" => Self::ArmorCirclet,
+            "armor_necklace" => Self::ArmorNecklace,
+            "armor_ring" => Self::ArmorRing,
+            "armor_default" => Self::ArmorDefault,
+            "scroll" => Self::Scroll,
+            "arrow" => Self::Arrow,
+            "hand_to_hand" => Self::HandToHand,
+            "potion_stamina" => Self::PotionStamina,
+            "potion_magicka" => Self::PotionMagicka,
+            "potion_fire_resist" => Self::PotionFireResist,
+            "potion_shock_resist" => Self::PotionShockResist,
+            "potion_frost_resist" => Self::PotionFrostResist,
+            "potion_magic_resist" => Self::PotionMagicResist,
+            "alteration" => Self::Alteration,
+            "conjuration" => Self::Conjuration,
+            "illusion" => Self::Illusion,
+            "torch" => Self::Torch,
+            "lantern" => Self::Lantern,
+            "mask" => Self::Mask,
+            "armor_rating" => Self::ArmorRating,
+            "weapon_damage" => Self::WeaponDamage,
+            "slot_background" => Self::SlotBackground,
+            "slot_highlighted_background" => Self::SlotHighlightedBackground,
+            "slot_active_background" => Self::SlotActiveBackground,
+            "wheel_background" => Self::WheelBackground,
+            "wheel_indicator_active" => Self::WheelIndicatorActive,
+            "wheel_indicator_inactive" => Self::WheelIndicatorInactive,
+            unknown => {
+                return Err(format!(
+                    "There is no variant corresponding to that icon file stem: {unknown}."
+                ));
+            }
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::tint::TintPalette;
+use super::{ICON_CUSTOM_DIRECTORY, ICON_DIRECTORY};
+
+/// The built-in icon set. It is `resources/icons` itself, and always the last of the fallback chain.
+pub const DEFAULT_THEME: &str = "default";
+
+/// Optional manifest file in each theme directory.
+const THEME_MANIFEST_FILE: &str = "theme.toml";
+
+/// `resources/icons/<theme>/theme.toml`
+///
+/// ```toml
+/// name = "Dark Souls"
+/// author = "someone"
+/// fallback = "minimal" # Another theme id. Omitted means `default`.
+///
+/// [tint] # See `TintPalette`
+/// hovered = "#FFD700"
+/// ```
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct ThemeManifest {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub fallback: Option<String>,
+    pub tint: Option<TintPalette>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Theme {
+    /// Directory name of the theme.
+    pub id: String,
+    pub manifest: ThemeManifest,
+}
+
+impl Theme {
+    fn new(icons_root: &Path, id: &str) -> Self {
+        let manifest = if id == DEFAULT_THEME {
+            ThemeManifest::default()
+        } else {
+            read_manifest(&icons_root.join(id).join(THEME_MANIFEST_FILE))
+        };
+
+        Self {
+            id: id.to_string(),
+            manifest,
+        }
+    }
+
+    #[inline]
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_THEME
+    }
+
+    /// Directory of icons named by `IconImageType`.
+    pub fn icon_dir(&self) -> PathBuf {
+        match self.is_default() {
+            true => PathBuf::from(ICON_DIRECTORY),
+            false => Path::new(ICON_DIRECTORY).join(&self.id),
+        }
+    }
+
+    /// Directory of `FID_`/`KWD_` custom icons.
+    ///
+    /// The default theme uses `resources/icons_custom`, others use `resources/icons/<theme>/custom`.
+    pub fn custom_icon_dir(&self) -> PathBuf {
+        match self.is_default() {
+            true => PathBuf::from(ICON_CUSTOM_DIRECTORY),
+            false => self.icon_dir().join("custom"),
+        }
+    }
+
+    /// Display name for logs and UI.
+    pub fn display_name(&self) -> &str {
+        self.manifest.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+fn read_manifest(path: &Path) -> ThemeManifest {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(_) => return ThemeManifest::default(), // The manifest is optional.
+    };
+
+    toml::from_str(&text).unwrap_or_else(|err| {
+        tracing::error!("Failed to parse theme manifest. path: {path:?}, error: {err}");
+        ThemeManifest::default()
+    })
+}
+
+/// Returns the themes in lookup order. The first is `active`, the last is always [`DEFAULT_THEME`].
+///
+/// Unknown themes and cyclic fallbacks are skipped with an error log.
+pub(crate) fn resolve_theme_chain(active: &str) -> Vec<Theme> {
+    resolve_theme_chain_in(Path::new(ICON_DIRECTORY), active)
+}
+
+fn resolve_theme_chain_in(icons_root: &Path, active: &str) -> Vec<Theme> {
+    let mut chain = Vec::new();
+    let mut visited = HashSet::new();
+    let mut next = Some(active.to_string());
+
+    while let Some(id) = next.take() {
+        if id == DEFAULT_THEME {
+            break;
+        }
+        if !visited.insert(id.clone()) {
+            tracing::error!("Cyclic icon theme fallback detected at `{id}`. Fall back to default.");
+            break;
+        }
+        if !icons_root.join(&id).is_dir() {
+            tracing::error!("Not found icon theme `{id}`. Fall back to default.");
+            break;
+        }
+
+        let theme = Theme::new(icons_root, &id);
+        next = Some(
+            theme
+                .manifest
+                .fallback
+                .clone()
+                .unwrap_or_else(|| DEFAULT_THEME.to_string()),
+        );
+        chain.push(theme);
+    }
+
+    chain.push(Theme::new(icons_root, DEFAULT_THEME));
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// `resources/icons` replacement under the system temp directory, removed on drop.
+    struct IconsRoot(PathBuf);
+
+    impl IconsRoot {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir()
+                .join(format!("wheeler_theme_test_{}_{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+
+        /// Adds `<id>/theme.toml` with an optional `fallback`.
+        fn theme(&self, id: &str, fallback: Option<&str>) -> &Self {
+            let dir = self.0.join(id);
+            std::fs::create_dir_all(&dir).unwrap();
+            let manifest = match fallback {
+                Some(fallback) => format!("name = \"{id} theme\"\nfallback = \"{fallback}\"\n"),
+                None => format!("name = \"{id} theme\"\n"),
+            };
+            std::fs::write(dir.join(THEME_MANIFEST_FILE), manifest).unwrap();
+            self
+        }
+
+        fn chain(&self, active: &str) -> Vec<String> {
+            resolve_theme_chain_in(&self.0, active)
+                .into_iter()
+                .map(|theme| theme.id)
+                .collect()
+        }
+    }
+
+    impl Drop for IconsRoot {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn follows_fallbacks_in_order() {
+        let root = IconsRoot::new("order");
+        root.theme("souls", Some("minimal")).theme("minimal", None);
+
+        assert_eq!(root.chain("souls"), ["souls", "minimal", DEFAULT_THEME]);
+
+        let chain = resolve_theme_chain_in(&root.0, "souls");
+        assert_eq!(chain[0].display_name(), "souls theme");
+        assert_eq!(chain[2].display_name(), DEFAULT_THEME);
+    }
+
+    #[test]
+    fn default_is_always_last() {
+        let root = IconsRoot::new("default");
+        root.theme("minimal", None);
+
+        assert_eq!(root.chain(DEFAULT_THEME), [DEFAULT_THEME]);
+        assert_eq!(root.chain("minimal"), ["minimal", DEFAULT_THEME]);
+
+        // A theme directory without manifest falls back to default, too.
+        std::fs::create_dir_all(root.0.join("bare")).unwrap();
+        assert_eq!(root.chain("bare"), ["bare", DEFAULT_THEME]);
+    }
+
+    #[test]
+    fn stops_at_cycles() {
+        let root = IconsRoot::new("cycle");
+        root.theme("a", Some("b"))
+            .theme("b", Some("a"))
+            .theme("self", Some("self"));
+
+        assert_eq!(root.chain("a"), ["a", "b", DEFAULT_THEME]);
+        assert_eq!(root.chain("self"), ["self", DEFAULT_THEME]);
+    }
+
+    #[test]
+    fn skips_missing_themes() {
+        let root = IconsRoot::new("missing");
+        root.theme("souls", Some("gone"));
+
+        assert_eq!(root.chain("gone"), [DEFAULT_THEME]);
+        assert_eq!(root.chain("souls"), ["souls", DEFAULT_THEME]);
+    }
+}
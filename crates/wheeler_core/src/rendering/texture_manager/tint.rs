@@ -0,0 +1,231 @@
+use std::sync::{LazyLock, RwLock};
+
+use crate::rendering::color::Color;
+
+static TINT_PALETTE: LazyLock<RwLock<TintPalette>> =
+    LazyLock::new(|| RwLock::new(TintPalette::default()));
+
+/// Display state of an icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IconState {
+    Normal,
+    Hovered,
+    Equipped,
+    /// e.g. Not in the inventory, not enough magicka.
+    Unavailable,
+}
+
+/// Magic school or damage element that gives an icon its accent color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Element {
+    Fire,
+    Frost,
+    Shock,
+    Alteration,
+    Conjuration,
+    Destruction,
+    Illusion,
+    Restoration,
+}
+
+/// `[tint]` table of `theme.toml`.
+///
+/// Monochrome svg icons using `currentColor` (or `marker` color) are recolored by these colors.
+///
+/// ```toml
+/// [tint]
+/// marker = "#FF00FF"
+/// normal = "#FFFFFF"
+/// hovered = "#FFE9A8"
+/// equipped = "#8FD3FF"
+/// unavailable = "#7F7F7F"
+///
+/// [tint.element]
+/// fire = "#FF5A36"
+/// ```
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct TintPalette {
+    /// Fill/stroke color in svg to be replaced in addition to `currentColor`.
+    pub marker: Option<Color>,
+    pub normal: Color,
+    pub hovered: Color,
+    pub equipped: Color,
+    pub unavailable: Color,
+    pub element: ElementPalette,
+}
+
+impl Default for TintPalette {
+    fn default() -> Self {
+        Self {
+            marker: None,
+            normal: Color::WHITE,
+            hovered: Color::rgb(0xFF, 0xE9, 0xA8),
+            equipped: Color::rgb(0x8F, 0xD3, 0xFF),
+            unavailable: Color::rgb(0x7F, 0x7F, 0x7F),
+            element: ElementPalette::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub(crate) struct ElementPalette {
+    pub fire: Color,
+    pub frost: Color,
+    pub shock: Color,
+    pub alteration: Color,
+    pub conjuration: Color,
+    pub destruction: Color,
+    pub illusion: Color,
+    pub restoration: Color,
+}
+
+impl Default for ElementPalette {
+    fn default() -> Self {
+        Self {
+            fire: Color::rgb(0xFF, 0x5A, 0x36),
+            frost: Color::rgb(0x7F, 0xD8, 0xFF),
+            shock: Color::rgb(0xB7, 0x8C, 0xFF),
+            alteration: Color::rgb(0xE0, 0xC8, 0x6A),
+            conjuration: Color::rgb(0x9B, 0x6B, 0xE8),
+            destruction: Color::rgb(0xFF, 0x7A, 0x45),
+            illusion: Color::rgb(0xD0, 0x7C, 0xE8),
+            restoration: Color::rgb(0xFF, 0xE4, 0x7A),
+        }
+    }
+}
+
+impl TintPalette {
+    #[inline]
+    pub const fn element(&self, element: Element) -> Color {
+        let palette = &self.element;
+        match element {
+            Element::Fire => palette.fire,
+            Element::Frost => palette.frost,
+            Element::Shock => palette.shock,
+            Element::Alteration => palette.alteration,
+            Element::Conjuration => palette.conjuration,
+            Element::Destruction => palette.destruction,
+            Element::Illusion => palette.illusion,
+            Element::Restoration => palette.restoration,
+        }
+    }
+
+    /// Decide the final color.
+    ///
+    /// - `accent`: element color or `tint` of `mapping.toml`. If `None`, the state color is used as is.
+    /// - Unavailable always wins, so that it can be told apart at a glance.
+    /// - With an accent, hovered lightens it and equipped shifts it toward the equipped color.
+    pub fn resolve(&self, state: IconState, accent: Option<Color>) -> Color {
+        match (state, accent) {
+            (IconState::Unavailable, _) => self.unavailable,
+            (IconState::Normal, None) => self.normal,
+            (IconState::Hovered, None) => self.hovered,
+            (IconState::Equipped, None) => self.equipped,
+            (IconState::Normal, Some(accent)) => accent,
+            (IconState::Hovered, Some(accent)) => mix(accent, Color::WHITE, 0.35),
+            (IconState::Equipped, Some(accent)) => mix(accent, self.equipped, 0.5),
+        }
+    }
+}
+
+/// Mix `color` with `other` by `ratio`(0.0..=1.0). Keeps the alpha of `color`.
+fn mix(color: Color, other: Color, ratio: f32) -> Color {
+    let mix = |c: u8, o: u8| (c as f32 + (o as f32 - c as f32) * ratio).round() as u8;
+    Color {
+        r: mix(color.r, other.r),
+        g: mix(color.g, other.g),
+        b: mix(color.b, other.b),
+        a: color.a,
+    }
+}
+
+pub(crate) fn palette() -> TintPalette {
+    TINT_PALETTE
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+pub(crate) fn set_palette(palette: TintPalette) {
+    *TINT_PALETTE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = palette;
+}
+
+const CURRENT_COLOR: &str = "currentColor";
+
+/// Does the svg have any color that can be replaced?
+pub(crate) fn is_recolorable(svg: &str, marker: Option<Color>) -> bool {
+    find_ignore_ascii_case(svg, CURRENT_COLOR).is_some()
+        || marker.is_some_and(|marker| find_ignore_ascii_case(svg, &hex_rgb(marker)).is_some())
+}
+
+/// Replace `currentColor` and `marker` color with `color`.
+///
+/// Replacing the text before parsing is enough, because usvg resolves both of them into plain colors.
+pub(crate) fn recolor_svg(svg: &str, marker: Option<Color>, color: Color) -> String {
+    let color = hex_rgb(color);
+    let svg = replace_ignore_ascii_case(svg, CURRENT_COLOR, &color);
+    match marker {
+        Some(marker) => replace_ignore_ascii_case(&svg, &hex_rgb(marker), &color),
+        None => svg,
+    }
+}
+
+/// `#RRGGBB` (svg fill does not accept `#RRGGBBAA` in all renderers)
+fn hex_rgb(color: Color) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b)
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn replace_ignore_ascii_case(haystack: &str, needle: &str, replacement: &str) -> String {
+    let mut ret = String::with_capacity(haystack.len());
+    let mut rest = haystack;
+    while let Some(index) = find_ignore_ascii_case(rest, needle) {
+        // `needle` is ascii, so `index` and `index + needle.len()` are char boundaries.
+        ret.push_str(&rest[..index]);
+        ret.push_str(replacement);
+        rest = &rest[index + needle.len()..];
+    }
+    ret.push_str(rest);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resolve_states() {
+        let palette = TintPalette::default();
+        let fire = palette.element(Element::Fire);
+
+        assert_eq!(palette.resolve(IconState::Normal, None), palette.normal);
+        assert_eq!(palette.resolve(IconState::Equipped, None), palette.equipped);
+        assert_eq!(
+            palette.resolve(IconState::Unavailable, Some(fire)),
+            palette.unavailable
+        );
+
+        assert_eq!(palette.resolve(IconState::Normal, Some(fire)), fire);
+        // #FF5A36 halfway to #8FD3FF
+        assert_eq!(
+            palette.resolve(IconState::Equipped, Some(fire)),
+            Color::rgb(0xC7, 0x97, 0x9B)
+        );
+        // #FF5A36 35% to white
+        assert_eq!(
+            palette.resolve(IconState::Hovered, Some(fire)),
+            Color::rgb(0xFF, 0x94, 0x7C)
+        );
+    }
+}
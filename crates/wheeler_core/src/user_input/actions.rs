@@ -0,0 +1,140 @@
+//! Wheeler actions bound to the keys of [`ControlsConfig`](crate::config::ControlsConfig).
+use std::sync::{Mutex, MutexGuard};
+
+use super::controls::{BindContext, BindingHandle, Chord, KeyEvent, Trigger};
+use super::rebind;
+use crate::config::{KeyBinding, NumberKeysConfig};
+use crate::wheeler::{EquipHand, Wheeler, edit, search, settings};
+
+/// Bindings made by [`bind_from_config`], to replace them on the next call.
+static HANDLES: Mutex<Vec<BindingHandle>> = Mutex::new(Vec::new());
+
+/// Bind all actions. Call this after [`crate::config::load`], and again after the controls change.
+pub fn bind_from_config() {
+    let old = core::mem::take(&mut *lock_handles());
+    old.into_iter().for_each(BindingHandle::unbind);
+
+    let config = crate::config::get();
+    let controls = &config.controls;
+    use BindContext::{EditMode, Settings, WheelClosed, WheelOpen};
+
+    bind(WheelClosed, &controls.toggle_wheel, Wheeler::open);
+    bind(WheelOpen, &controls.toggle_wheel, Wheeler::close);
+
+    bind(WheelOpen, &controls.activate_right, || {
+        Wheeler::activate_hovered(EquipHand::Right);
+    });
+    bind(WheelOpen, &controls.activate_left, || {
+        Wheeler::activate_hovered(EquipHand::Left);
+    });
+    bind(WheelOpen, &controls.next_item, next_item);
+    bind(WheelOpen, &controls.prev_item, prev_item);
+    bind(WheelOpen, &controls.next_wheel, || Wheeler::cycle_wheel(1));
+    bind(WheelOpen, &controls.prev_wheel, || Wheeler::cycle_wheel(-1));
+    bind(
+        WheelOpen,
+        &controls.toggle_edit_mode,
+        edit::toggle_edit_mode,
+    );
+    bind(WheelOpen, &controls.toggle_settings, settings::toggle);
+    bind(WheelOpen, &controls.search, search::start);
+    bind_number_keys(&controls.number_keys);
+
+    bind(EditMode, &controls.add_item, edit::add_selected_item);
+    bind(EditMode, &controls.add_loadout, edit::add_equipped_loadout);
+    bind(EditMode, &controls.add_armor_set, edit::add_worn_armor_set);
+    bind(
+        EditMode,
+        &controls.toggle_unequip_uncovered,
+        edit::toggle_unequip_uncovered,
+    );
+    bind(EditMode, &controls.add_smart_item, edit::add_smart_item);
+    bind(EditMode, &controls.remove_item, edit::remove_hovered_item);
+    bind(EditMode, &controls.insert_entry, edit::insert_entry);
+    bind(EditMode, &controls.remove_entry, edit::remove_hovered_entry);
+    bind(EditMode, &controls.add_wheel, edit::add_wheel);
+    bind(EditMode, &controls.remove_wheel, edit::remove_active_wheel);
+    bind(EditMode, &controls.undo, edit::undo);
+    bind(EditMode, &controls.redo, edit::redo);
+
+    // Shadow the wheel, so that e.g. clicking the window doesn't equip the hovered item.
+    bind(Settings, &controls.next_item, settings::select_next_row);
+    bind(Settings, &controls.prev_item, settings::select_prev_row);
+    bind(Settings, &controls.next_wheel, settings::select_next_row);
+    bind(Settings, &controls.prev_wheel, settings::select_prev_row);
+    bind_event(Settings, &controls.activate_right, |event| {
+        settings::activate_selected(event.device);
+    });
+    bind(Settings, &controls.activate_left, rebind::cancel);
+    bind_event(Settings, &controls.remove_item, |event| {
+        settings::clear_selected(event.device);
+    });
+}
+
+/// Run `action` on the trigger of the keyboard or gamepad chord of `binding` in `context`.
+fn bind<F>(context: BindContext, binding: &KeyBinding, action: F)
+where
+    F: Fn() + Clone + Send + Sync + 'static,
+{
+    for chord in binding.chords() {
+        bind_chord(context, chord, binding.on, action.clone());
+    }
+}
+
+/// Same as [`bind`], but `action` gets the key event. (e.g. to tell the device)
+fn bind_event<F>(context: BindContext, binding: &KeyBinding, action: F)
+where
+    F: Fn(&KeyEvent) + Clone + Send + Sync + 'static,
+{
+    for chord in binding.chords() {
+        let handle = super::controls::bind(context, chord, binding.on, action.clone());
+        lock_handles().push(handle);
+    }
+}
+
+/// Number keys equip the entries of the active wheel. With the modifier, to the left hand.
+fn bind_number_keys(config: &NumberKeysConfig) {
+    if !config.enabled {
+        return;
+    }
+    for (index, key) in NumberKeysConfig::KEYS.into_iter().enumerate() {
+        bind_chord(BindContext::WheelOpen, key, Trigger::Down, move || {
+            Wheeler::activate_entry(index, EquipHand::Right);
+        });
+        if let Some(modifier) = config.left_hand_modifier {
+            let chord = Chord::new([modifier], key);
+            bind_chord(BindContext::WheelOpen, chord, Trigger::Down, move || {
+                Wheeler::activate_entry(index, EquipHand::Left);
+            });
+        }
+    }
+}
+
+fn bind_chord<F>(context: BindContext, chord: impl Into<Chord>, on: Trigger, action: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let handle = super::controls::bind(context, chord, on, move |_| action());
+    lock_handles().push(handle);
+}
+
+fn lock_handles() -> MutexGuard<'static, Vec<BindingHandle>> {
+    HANDLES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Also by the mouse wheel, which moves the row of the settings window instead while it is open.
+pub(crate) fn next_item() {
+    match settings::is_open() {
+        true => settings::select_next_row(),
+        false => Wheeler::cycle_hovered_item(1),
+    }
+}
+
+pub(crate) fn prev_item() {
+    match settings::is_open() {
+        true => settings::select_prev_row(),
+        false => Wheeler::cycle_hovered_item(-1),
+    }
+}
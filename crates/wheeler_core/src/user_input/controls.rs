@@ -1,45 +1,520 @@
+//! Key bindings. A binding is a closure that runs when its key is pressed or released in its context.
+//!
+//! A binding can require modifier keys held. (e.g. `Shift+Q`) Of the bindings whose modifiers are
+//! all held, only the most specific run, so `Shift+Q` suppresses `Q`.
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::Instant;
+
 use dashmap::DashMap;
-use std::sync::LazyLock;
+
+use super::gesture::Recognizer;
+use super::input::Device;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct KeyId(pub u32);
 
-pub type FnMap = DashMap<KeyId, fn()>;
+/// Keys that must be held, then `key`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord {
+    /// Sorted and deduplicated.
+    modifiers: Vec<KeyId>,
+    pub key: KeyId,
+}
 
-static KEY_FN_MAP_DOWN: LazyLock<FnMap> = LazyLock::new(FnMap::new);
-static KEY_FN_MAP_UP: LazyLock<FnMap> = LazyLock::new(FnMap::new);
-static KEY_FN_MAP_DOWN_GAMEPAD: LazyLock<FnMap> = LazyLock::new(FnMap::new);
-static KEY_FN_MAP_UP_GAMEPAD: LazyLock<FnMap> = LazyLock::new(FnMap::new);
+impl Chord {
+    pub fn new(modifiers: impl IntoIterator<Item = KeyId>, key: KeyId) -> Self {
+        let mut modifiers: Vec<_> = modifiers.into_iter().filter(|m| *m != key).collect();
+        modifiers.sort_unstable();
+        modifiers.dedup();
+        Self { modifiers, key }
+    }
 
-pub fn bind_input(key: KeyId, func: fn(), is_down: bool, is_gamepad: bool) {
-    match (is_down, is_gamepad) {
-        (true, true) => KEY_FN_MAP_DOWN_GAMEPAD.insert(key, func),
-        (true, false) => KEY_FN_MAP_DOWN.insert(key, func),
-        (false, true) => KEY_FN_MAP_UP_GAMEPAD.insert(key, func),
-        (false, false) => KEY_FN_MAP_UP.insert(key, func),
-    };
+    #[inline]
+    pub fn modifiers(&self) -> &[KeyId] {
+        &self.modifiers
+    }
 }
 
-/// Is already registered key?
-pub fn is_key_bound(key: KeyId) -> bool {
-    KEY_FN_MAP_DOWN_GAMEPAD.contains_key(&key)
-        | KEY_FN_MAP_DOWN.contains_key(&key)
-        | KEY_FN_MAP_UP_GAMEPAD.contains_key(&key)
-        | KEY_FN_MAP_UP.contains_key(&key)
+impl From<KeyId> for Chord {
+    #[inline]
+    fn from(key: KeyId) -> Self {
+        Self {
+            modifiers: Vec::new(),
+            key,
+        }
+    }
 }
 
-pub fn dispatch(key: KeyId, is_down: bool, is_gamepad: bool) -> bool {
-    let func = match (is_down, is_gamepad) {
-        (true, true) => KEY_FN_MAP_DOWN_GAMEPAD.get(&key),
-        (true, false) => KEY_FN_MAP_DOWN.get(&key),
-        (false, true) => KEY_FN_MAP_UP_GAMEPAD.get(&key),
-        (false, false) => KEY_FN_MAP_UP.get(&key),
-    };
+/// Set of [`KeyId`]s. Ids out of range are never contained.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeySet([u64; 5]);
+
+impl KeySet {
+    #[inline]
+    pub const fn new() -> Self {
+        Self([0; 5])
+    }
+
+    #[inline]
+    const fn position(key: KeyId) -> Option<(usize, u64)> {
+        let index = key.0 as usize / 64;
+        if index < 5 {
+            Some((index, 1 << (key.0 % 64)))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn insert(&mut self, key: KeyId) {
+        if let Some((index, bit)) = Self::position(key) {
+            self.0[index] |= bit;
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, key: KeyId) {
+        if let Some((index, bit)) = Self::position(key) {
+            self.0[index] &= !bit;
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, key: KeyId) -> bool {
+        Self::position(key).is_some_and(|(index, bit)| self.0[index] & bit != 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = KeyId> + '_ {
+        (0..self.0.len() as u32 * 64)
+            .map(KeyId)
+            .filter(|key| self.contains(*key))
+    }
+}
+
+/// Where a binding is active. The same key can be bound in each context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindContext {
+    WheelClosed,
+    WheelOpen,
+    /// Wheel open in the edit mode. Keys not bound here fall back to [`Self::WheelOpen`].
+    EditMode,
+    /// Settings window open over the wheel. Keys not bound here fall back to the wheel contexts.
+    ///
+    /// The activate keys drive the window here, so that they don't equip the hovered item.
+    Settings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    Down,
+    /// Still down. Sent every frame after [`Self::Down`].
+    Held,
+    Up,
+}
+
+/// When a binding runs. Gestures are timed by [`GestureConfig`](crate::config::GestureConfig).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// Pressed
+    #[default]
+    Down,
+    /// Released
+    Up,
+    /// Released before the long press.
+    ///
+    /// If the key also has a [`Self::DoubleTap`] binding, this waits for the double tap window.
+    Tap,
+    /// Second tap soon after a tap.
+    DoubleTap,
+    /// Held past the threshold. Runs while still held.
+    LongPress,
+    /// Released after the long press.
+    HoldRelease,
+}
+
+/// Passed to the bound closure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyEvent {
+    pub key: KeyId,
+    pub device: Device,
+    pub state: KeyState,
+    /// Seconds the key has been held. `0.0` on [`KeyState::Down`].
+    pub held_secs: f32,
+    /// Other keys held down at the time.
+    pub held: KeySet,
+}
+
+type Callback = Arc<dyn Fn(&KeyEvent) + Send + Sync>;
+
+struct Binding {
+    id: u64,
+    modifiers: Vec<KeyId>,
+    on: Trigger,
+    callback: Callback,
+}
+
+impl Binding {
+    #[inline]
+    fn modifiers_held(&self, held: &KeySet) -> bool {
+        self.modifiers.iter().all(|key| held.contains(*key))
+    }
+}
+
+static BINDINGS: LazyLock<DashMap<(BindContext, KeyId), Vec<Binding>>> =
+    LazyLock::new(DashMap::new);
+static NEXT_BINDING_ID: AtomicU64 = AtomicU64::new(0);
+
+static GESTURES: LazyLock<Mutex<Recognizer>> = LazyLock::new(|| Mutex::new(Recognizer::new()));
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Returned by [`bind`]. Dropping this keeps the binding.
+#[derive(Debug)]
+pub struct BindingHandle {
+    id: u64,
+    context: BindContext,
+    key: KeyId,
+}
+
+impl BindingHandle {
+    /// Remove the binding.
+    pub fn unbind(self) {
+        let slot = (self.context, self.key);
+        if let Some(mut bindings) = BINDINGS.get_mut(&slot) {
+            bindings.retain(|binding| binding.id != self.id);
+        }
+        BINDINGS.remove_if(&slot, |_, bindings| bindings.is_empty());
+    }
+}
+
+/// Run `callback` on `on` of the key of `chord` in `context` while the modifiers are held.
+pub fn bind<F>(
+    context: BindContext,
+    chord: impl Into<Chord>,
+    on: Trigger,
+    callback: F,
+) -> BindingHandle
+where
+    F: Fn(&KeyEvent) + Send + Sync + 'static,
+{
+    let Chord { modifiers, key } = chord.into();
+    let id = NEXT_BINDING_ID.fetch_add(1, Ordering::Relaxed);
+    BINDINGS.entry((context, key)).or_default().push(Binding {
+        id,
+        modifiers,
+        on,
+        callback: Arc::new(callback),
+    });
+    BindingHandle { id, context, key }
+}
+
+/// Contexts of the current state, in the order of priority.
+pub fn active_contexts() -> &'static [BindContext] {
+    contexts_of(
+        crate::wheeler::is_wheeler_open(),
+        crate::wheeler::settings::is_open(),
+        crate::wheeler::edit::is_edit_mode(),
+    )
+}
+
+const fn contexts_of(
+    is_wheeler_open: bool,
+    is_settings_open: bool,
+    is_edit_mode: bool,
+) -> &'static [BindContext] {
+    use BindContext::{EditMode, Settings, WheelClosed, WheelOpen};
+
+    match (is_wheeler_open, is_settings_open, is_edit_mode) {
+        (false, _, _) => &[WheelClosed],
+        (true, false, false) => &[WheelOpen],
+        (true, false, true) => &[EditMode, WheelOpen],
+        (true, true, false) => &[Settings, WheelOpen],
+        (true, true, true) => &[Settings, EditMode, WheelOpen],
+    }
+}
+
+/// Is `key` bound in `context` with modifiers all in `held`?
+pub fn is_key_bound(context: BindContext, key: KeyId, held: &KeySet) -> bool {
+    BINDINGS
+        .get(&(context, key))
+        .is_some_and(|bindings| most_specific(&bindings, held).is_some())
+}
+
+/// Number of modifiers of the most specific bindings whose modifiers are all held.
+fn most_specific(bindings: &[Binding], held: &KeySet) -> Option<usize> {
+    bindings
+        .iter()
+        .filter(|binding| binding.modifiers_held(held))
+        .map(|binding| binding.modifiers.len())
+        .max()
+}
+
+/// Run the bindings of `event` and of the gestures it completes.
+///
+/// Returns `true` if the key is bound. (even if no binding is for this state)
+pub fn dispatch(contexts: &[BindContext], event: &KeyEvent) -> bool {
+    let is_bound = contexts
+        .iter()
+        .any(|context| is_key_bound(*context, event.key, &event.held));
+    if !is_bound {
+        return false;
+    }
+
+    let wait_double_tap = !find_callbacks(contexts, event, Trigger::DoubleTap).is_empty();
+    let config = crate::config::get().controls.gestures;
+    let gestures = lock_gestures().on_event(event, now_secs(), wait_double_tap, &config);
+
+    match event.state {
+        KeyState::Down => run(contexts, event, Trigger::Down),
+        KeyState::Up => run(contexts, event, Trigger::Up),
+        KeyState::Held => {}
+    }
+    for (event, trigger) in gestures {
+        run(contexts, &event, trigger);
+    }
+    true
+}
+
+/// Run the taps that no double tap followed. Call this every frame.
+pub fn tick() {
+    let config = crate::config::get().controls.gestures;
+    let taps = lock_gestures().tick(now_secs(), &config);
+    for (event, trigger) in taps {
+        run(active_contexts(), &event, trigger);
+    }
+}
+
+fn run(contexts: &[BindContext], event: &KeyEvent, trigger: Trigger) {
+    for callback in find_callbacks(contexts, event, trigger) {
+        callback(event);
+    }
+}
 
-    if let Some(func) = func {
-        func();
-        return true;
+/// Callbacks for `trigger` of the most specific bindings of `event.key`, in the first of `contexts`
+/// that binds it.
+///
+/// Cloned, so that callbacks can bind or unbind.
+fn find_callbacks(contexts: &[BindContext], event: &KeyEvent, trigger: Trigger) -> Vec<Callback> {
+    let Some((bindings, specificity)) = contexts.iter().find_map(|context| {
+        let bindings = BINDINGS.get(&(*context, event.key))?;
+        let specificity = most_specific(&bindings, &event.held)?;
+        Some((bindings, specificity))
+    }) else {
+        return Vec::new();
     };
-    false
+
+    bindings
+        .iter()
+        .filter(|binding| binding.on == trigger && binding.modifiers.len() == specificity)
+        .filter(|binding| binding.modifiers_held(&event.held))
+        .map(|binding| Arc::clone(&binding.callback))
+        .collect()
+}
+
+fn lock_gestures() -> MutexGuard<'static, Recognizer> {
+    GESTURES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Seconds since the first call.
+fn now_secs() -> f64 {
+    START.elapsed().as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const Q: KeyId = KeyId(0x10);
+    const E: KeyId = KeyId(0x12);
+    const L_SHIFT: KeyId = KeyId(0x2A);
+    const L_CTRL: KeyId = KeyId(0x1D);
+    const PAD_LB: KeyId = KeyId(KeyId::GAMEPAD_OFFSET + 8);
+    const PAD_A: KeyId = KeyId(KeyId::GAMEPAD_OFFSET + 10);
+
+    /// Bindings are global, so each test binds in its own context.
+    struct Recorder {
+        log: Arc<Mutex<Vec<&'static str>>>,
+        handles: Vec<BindingHandle>,
+    }
+
+    impl Recorder {
+        fn new() -> Self {
+            Self {
+                log: Arc::default(),
+                handles: Vec::new(),
+            }
+        }
+
+        fn bind(&mut self, context: BindContext, chord: Chord, on: Trigger, name: &'static str) {
+            let log = Arc::clone(&self.log);
+            self.handles.push(bind(context, chord, on, move |_| {
+                log.lock().unwrap().push(name);
+            }));
+        }
+
+        /// Names of the callbacks run for `key` pressed while `held`.
+        fn press(
+            &self,
+            contexts: &[BindContext],
+            device: Device,
+            key: KeyId,
+            held: &[KeyId],
+        ) -> Vec<&'static str> {
+            let mut held_set = KeySet::new();
+            held.iter().for_each(|key| held_set.insert(*key));
+            let event = KeyEvent {
+                key,
+                device,
+                state: KeyState::Down,
+                held_secs: 0.0,
+                held: held_set,
+            };
+            for callback in find_callbacks(contexts, &event, Trigger::Down) {
+                callback(&event);
+            }
+            core::mem::take(&mut *self.log.lock().unwrap())
+        }
+    }
+
+    impl Drop for Recorder {
+        fn drop(&mut self) {
+            self.handles.drain(..).for_each(BindingHandle::unbind);
+        }
+    }
+
+    #[test]
+    fn chord_suppresses_plain_key() {
+        const CONTEXT: BindContext = BindContext::WheelClosed;
+        let mut recorder = Recorder::new();
+        recorder.bind(CONTEXT, Q.into(), Trigger::Down, "Q");
+        recorder.bind(CONTEXT, Chord::new([L_SHIFT], Q), Trigger::Down, "Shift+Q");
+        recorder.bind(CONTEXT, Chord::new([L_SHIFT], Q), Trigger::Up, "Shift+Q up");
+
+        let press = |held: &[KeyId]| recorder.press(&[CONTEXT], Device::Keyboard, Q, held);
+        assert_eq!(press(&[]), ["Q"]);
+        assert_eq!(press(&[L_SHIFT]), ["Shift+Q"]);
+        // Extra held keys don't matter.
+        assert_eq!(press(&[L_SHIFT, L_CTRL]), ["Shift+Q"]);
+        assert!(is_key_bound(CONTEXT, Q, &KeySet::new()));
+    }
+
+    #[test]
+    fn gamepad_chord_suppresses_plain_button() {
+        const CONTEXT: BindContext = BindContext::WheelOpen;
+        let mut recorder = Recorder::new();
+        recorder.bind(CONTEXT, PAD_A.into(), Trigger::Down, "A");
+        recorder.bind(CONTEXT, Chord::new([PAD_LB], PAD_A), Trigger::Down, "LB+A");
+
+        let press = |held: &[KeyId]| recorder.press(&[CONTEXT], Device::Gamepad, PAD_A, held);
+        assert_eq!(press(&[]), ["A"]);
+        assert_eq!(press(&[PAD_LB]), ["LB+A"]);
+
+        // The first context binding the key wins, and falls back to the next one otherwise.
+        let contexts = [BindContext::EditMode, CONTEXT];
+        assert_eq!(
+            recorder.press(&contexts, Device::Gamepad, PAD_A, &[PAD_LB]),
+            ["LB+A"]
+        );
+    }
+
+    #[test]
+    fn settings_shadows_wheel_open() {
+        use BindContext::{Settings, WheelOpen};
+        const MOUSE_LEFT: KeyId = KeyId(KeyId::MOUSE_OFFSET);
+        let mut recorder = Recorder::new();
+        recorder.bind(WheelOpen, MOUSE_LEFT.into(), Trigger::Down, "equip");
+        recorder.bind(WheelOpen, E.into(), Trigger::Down, "E");
+        recorder.bind(Settings, MOUSE_LEFT.into(), Trigger::Down, "cancel");
+
+        let settings = contexts_of(true, true, false);
+        assert_eq!(settings, [Settings, WheelOpen]);
+        let press = |key| recorder.press(settings, Device::Mouse, key, &[]);
+        assert_eq!(press(MOUSE_LEFT), ["cancel"]);
+        // Not bound in the settings context.
+        assert_eq!(press(E), ["E"]);
+
+        let wheel_open = contexts_of(true, false, false);
+        assert_eq!(
+            recorder.press(wheel_open, Device::Mouse, MOUSE_LEFT, &[]),
+            ["equip"]
+        );
+    }
+
+    #[test]
+    fn contexts_of_state() {
+        use BindContext::{EditMode, Settings, WheelClosed, WheelOpen};
+
+        // The settings window is only shown over the open wheel.
+        assert_eq!(contexts_of(false, true, true), [WheelClosed]);
+        assert_eq!(contexts_of(true, false, true), [EditMode, WheelOpen]);
+        assert_eq!(
+            contexts_of(true, true, true),
+            [Settings, EditMode, WheelOpen]
+        );
+    }
+
+    #[test]
+    fn unmatched_modifier() {
+        const CONTEXT: BindContext = BindContext::EditMode;
+        let mut recorder = Recorder::new();
+        recorder.bind(CONTEXT, Chord::new([L_SHIFT], E), Trigger::Down, "Shift+E");
+
+        // `Ctrl` is no modifier of the bindings. `Shift+E` still needs `Shift`.
+        let held = [L_CTRL];
+        assert_eq!(
+            recorder.press(&[CONTEXT], Device::Keyboard, E, &held),
+            Vec::<&str>::new()
+        );
+        let mut held_set = KeySet::new();
+        held_set.insert(L_CTRL);
+        assert!(!is_key_bound(CONTEXT, E, &held_set));
+
+        // A plain binding runs with any unrelated key held.
+        recorder.bind(CONTEXT, E.into(), Trigger::Down, "E");
+        assert_eq!(
+            recorder.press(&[CONTEXT], Device::Keyboard, E, &held),
+            ["E"]
+        );
+        assert!(is_key_bound(CONTEXT, E, &held_set));
+    }
+
+    #[test]
+    fn chord_new_sorts_and_dedups() {
+        let chord = Chord::new([L_SHIFT, L_CTRL, L_SHIFT, Q], Q);
+        assert_eq!(chord.modifiers(), [L_CTRL, L_SHIFT]);
+        assert_eq!(chord.key, Q);
+        assert_eq!(chord, Chord::new([L_CTRL, L_SHIFT], Q));
+        assert_eq!(Chord::from(Q), Chord::new([], Q));
+    }
+
+    #[test]
+    fn most_specific_count() {
+        let binding = |modifiers: Vec<KeyId>| Binding {
+            id: 0,
+            modifiers,
+            on: Trigger::Down,
+            callback: Arc::new(|_| {}),
+        };
+        let bindings = [
+            binding(vec![]),
+            binding(vec![L_SHIFT]),
+            binding(vec![L_CTRL, L_SHIFT]),
+        ];
+        let held = |keys: &[KeyId]| {
+            let mut set = KeySet::new();
+            keys.iter().for_each(|key| set.insert(*key));
+            set
+        };
+        assert_eq!(most_specific(&bindings, &held(&[])), Some(0));
+        assert_eq!(most_specific(&bindings, &held(&[L_SHIFT])), Some(1));
+        assert_eq!(most_specific(&bindings, &held(&[L_CTRL])), Some(0));
+        assert_eq!(most_specific(&bindings, &held(&[L_CTRL, L_SHIFT])), Some(2));
+        assert_eq!(most_specific(&bindings[1..], &held(&[L_CTRL])), None);
+        assert_eq!(most_specific(&[], &held(&[L_SHIFT])), None);
+    }
 }
@@ -0,0 +1,239 @@
+//! Tap, double tap, long press and hold release of keys.
+//!
+//! [`Recognizer`] doesn't read the clock. The caller passes the time, so the tests can replay any timing.
+use std::collections::HashMap;
+
+use super::controls::{KeyEvent, KeyId, KeyState, Trigger};
+use crate::config::GestureConfig;
+
+/// Gesture state of each key.
+#[derive(Debug, Default)]
+pub(crate) struct Recognizer {
+    keys: HashMap<KeyId, Tracker>,
+}
+
+#[derive(Debug, Default)]
+struct Tracker {
+    /// [`Trigger::LongPress`] is already sent for this press.
+    long_pressed: bool,
+    /// This press came soon after a tap.
+    second_press: bool,
+    /// Tap waiting to see if a second tap follows. (released at, event)
+    pending_tap: Option<(f64, KeyEvent)>,
+}
+
+impl Recognizer {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed an event of a key. `now` is in seconds.
+    ///
+    /// - `wait_double_tap`: the key has a double tap binding, so its taps wait for
+    ///   [`GestureConfig::double_tap_secs`] before being sent.
+    ///
+    /// Returns the gestures completed by this event.
+    pub(crate) fn on_event(
+        &mut self,
+        event: &KeyEvent,
+        now: f64,
+        wait_double_tap: bool,
+        config: &GestureConfig,
+    ) -> Vec<(KeyEvent, Trigger)> {
+        let mut gestures = Vec::new();
+        let tracker = self.keys.entry(event.key).or_default();
+
+        match event.state {
+            KeyState::Down => {
+                tracker.long_pressed = false;
+                tracker.second_press = false;
+                if let Some((released_at, tap)) = tracker.pending_tap.take() {
+                    match now - released_at <= f64::from(config.double_tap_secs) {
+                        true => tracker.second_press = true,
+                        // `tick` was not called in time.
+                        false => gestures.push((tap, Trigger::Tap)),
+                    }
+                }
+            }
+            KeyState::Held => {
+                if !tracker.long_pressed && event.held_secs >= config.long_press_secs {
+                    tracker.long_pressed = true;
+                    gestures.push((*event, Trigger::LongPress));
+                }
+            }
+            KeyState::Up => {
+                if event.held_secs >= config.long_press_secs {
+                    if !tracker.long_pressed {
+                        gestures.push((*event, Trigger::LongPress));
+                    }
+                    gestures.push((*event, Trigger::HoldRelease));
+                } else if tracker.second_press {
+                    gestures.push((*event, Trigger::DoubleTap));
+                } else if wait_double_tap {
+                    tracker.pending_tap = Some((now, *event));
+                } else {
+                    gestures.push((*event, Trigger::Tap));
+                }
+                tracker.long_pressed = false;
+                tracker.second_press = false;
+            }
+        }
+        gestures
+    }
+
+    /// Send the taps that no second tap followed. Call this every frame.
+    pub(crate) fn tick(&mut self, now: f64, config: &GestureConfig) -> Vec<(KeyEvent, Trigger)> {
+        let double_tap_secs = f64::from(config.double_tap_secs);
+        self.keys
+            .values_mut()
+            .filter_map(|tracker| {
+                let (released_at, _) = tracker.pending_tap?;
+                if now - released_at <= double_tap_secs {
+                    return None;
+                }
+                let (_, tap) = tracker.pending_tap.take()?;
+                Some((tap, Trigger::Tap))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_input::controls::KeySet;
+    use crate::user_input::input::Device;
+    use pretty_assertions::assert_eq;
+
+    const KEY: KeyId = KeyId(0x1E);
+    const CONFIG: GestureConfig = GestureConfig {
+        long_press_secs: 0.5,
+        double_tap_secs: 0.3,
+    };
+
+    const fn event(state: KeyState, held_secs: f32) -> KeyEvent {
+        KeyEvent {
+            key: KEY,
+            device: Device::Keyboard,
+            state,
+            held_secs,
+            held: KeySet::new(),
+        }
+    }
+
+    /// Replay frames of `(time, event of the key)` and collect the gestures with their times.
+    fn replay(
+        frames: &[(f64, Option<(KeyState, f32)>)],
+        wait_double_tap: bool,
+    ) -> Vec<(f64, Trigger)> {
+        let mut recognizer = Recognizer::new();
+        let mut gestures = Vec::new();
+        for &(now, key_event) in frames {
+            // Like `process_and_filter`, tick before the events of the frame.
+            let taps = recognizer.tick(now, &CONFIG);
+            gestures.extend(taps.into_iter().map(|(_, trigger)| (now, trigger)));
+            if let Some((state, held_secs)) = key_event {
+                let event = event(state, held_secs);
+                let completed = recognizer.on_event(&event, now, wait_double_tap, &CONFIG);
+                gestures.extend(completed.into_iter().map(|(_, trigger)| (now, trigger)));
+            }
+        }
+        gestures
+    }
+
+    const fn down(now: f64) -> (f64, Option<(KeyState, f32)>) {
+        (now, Some((KeyState::Down, 0.0)))
+    }
+
+    const fn held(now: f64, held_secs: f32) -> (f64, Option<(KeyState, f32)>) {
+        (now, Some((KeyState::Held, held_secs)))
+    }
+
+    const fn up(now: f64, held_secs: f32) -> (f64, Option<(KeyState, f32)>) {
+        (now, Some((KeyState::Up, held_secs)))
+    }
+
+    #[test]
+    fn tap() {
+        let frames = [down(0.0), up(0.1, 0.1)];
+        assert_eq!(replay(&frames, false), [(0.1, Trigger::Tap)]);
+    }
+
+    #[test]
+    fn tap_waits_for_double_tap_window() {
+        let frames = [down(0.0), up(0.1, 0.1), (0.3, None), (0.5, None)];
+        assert_eq!(replay(&frames, true), [(0.5, Trigger::Tap)]);
+
+        // The event of the release is kept.
+        let mut recognizer = Recognizer::new();
+        recognizer.on_event(&event(KeyState::Down, 0.0), 0.0, true, &CONFIG);
+        recognizer.on_event(&event(KeyState::Up, 0.1), 0.1, true, &CONFIG);
+        assert_eq!(
+            recognizer.tick(0.5, &CONFIG),
+            [(event(KeyState::Up, 0.1), Trigger::Tap)]
+        );
+    }
+
+    #[test]
+    fn double_tap() {
+        let frames = [down(0.0), up(0.1, 0.1), down(0.25), up(0.3, 0.05)];
+        assert_eq!(replay(&frames, true), [(0.3, Trigger::DoubleTap)]);
+    }
+
+    #[test]
+    fn slow_second_press_is_two_taps() {
+        let frames = [
+            down(0.0),
+            up(0.1, 0.1),
+            down(0.6),
+            up(0.7, 0.1),
+            (1.1, None),
+        ];
+        assert_eq!(
+            replay(&frames, true),
+            [(0.6, Trigger::Tap), (1.1, Trigger::Tap)]
+        );
+    }
+
+    #[test]
+    fn pending_tap_is_sent_if_tick_is_late() {
+        let mut recognizer = Recognizer::new();
+        recognizer.on_event(&event(KeyState::Down, 0.0), 0.0, true, &CONFIG);
+        recognizer.on_event(&event(KeyState::Up, 0.1), 0.1, true, &CONFIG);
+        let gestures = recognizer.on_event(&event(KeyState::Down, 0.0), 0.6, true, &CONFIG);
+        assert_eq!(gestures, [(event(KeyState::Up, 0.1), Trigger::Tap)]);
+    }
+
+    #[test]
+    fn long_press_then_hold_release() {
+        let frames = [
+            down(0.0),
+            held(0.3, 0.3),
+            held(0.6, 0.6),
+            held(0.9, 0.9),
+            up(1.0, 1.0),
+        ];
+        assert_eq!(
+            replay(&frames, true),
+            [(0.6, Trigger::LongPress), (1.0, Trigger::HoldRelease)]
+        );
+    }
+
+    #[test]
+    fn long_press_without_held_frames() {
+        let frames = [down(0.0), up(0.8, 0.8)];
+        assert_eq!(
+            replay(&frames, false),
+            [(0.8, Trigger::LongPress), (0.8, Trigger::HoldRelease)]
+        );
+    }
+
+    #[test]
+    fn long_second_press_is_not_double_tap() {
+        let frames = [down(0.0), up(0.1, 0.1), down(0.2), up(0.8, 0.6)];
+        assert_eq!(
+            replay(&frames, true),
+            [(0.8, Trigger::LongPress), (0.8, Trigger::HoldRelease)]
+        );
+    }
+}
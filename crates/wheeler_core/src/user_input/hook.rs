@@ -0,0 +1,163 @@
+//! Hook of the input event dispatch, so that Wheeler sees the events before the game.
+use core::ffi::c_void;
+use core::ptr;
+use std::sync::OnceLock;
+
+use commonlibsse_ng::re::InputEvent::InputEvent;
+use commonlibsse_ng::rel::{id::RelocationID, offset::VariantOffset};
+
+use crate::hook::{Hook, HookError};
+
+/// `BSTEventSource<InputEvent*>::SendEvent`
+type SendEventFn = extern "C" fn(dispatcher: *mut c_void, events: *const *mut InputEvent);
+
+static INPUT_ORIGINAL_FN: OnceLock<SendEventFn> = OnceLock::new();
+
+/// Call this once on SKSE init.
+pub fn install() -> Result<(), InputHookError> {
+    InputHook::hook_start()
+}
+
+struct InputHook;
+impl InputHook {
+    fn hook_start() -> Result<(), InputHookError> {
+        let original_fn = unsafe {
+            let original_fn = crate::hook::write_thunk_call::<InputHook, VariantOffset>()?;
+            core::mem::transmute::<*const (), SendEventFn>(original_fn)
+        };
+        INPUT_ORIGINAL_FN
+            .set(original_fn)
+            .map_err(|_| InputHookError::FailedToSetInputOriginalFn)
+    }
+
+    extern "C" fn thunk(dispatcher: *mut c_void, events: *const *mut InputEvent) {
+        let Some(original_fn) = INPUT_ORIGINAL_FN.get() else {
+            return;
+        };
+        let Some(mut head) = (unsafe { events.as_ref() }).copied() else {
+            return original_fn(dispatcher, events);
+        };
+
+        let blocked = unsafe { super::input::process_and_filter(&mut head) };
+        if !blocked.is_empty() {
+            head = unsafe { unlink(head, &blocked) };
+        }
+
+        // Even if all events are blocked, send the empty list so that the game can reset its states.
+        original_fn(dispatcher, &head);
+    }
+}
+impl Hook<VariantOffset> for InputHook {
+    const ID: RelocationID = RelocationID::from_se_ae_id(67315, 68617);
+    const OFFSET: VariantOffset = VariantOffset::new(0x7B, 0x7B, 0x81);
+    const REPLACED_FN: *const () = Self::thunk as *const ();
+}
+
+/// Node of a singly linked list, such as `InputEvent`.
+trait LinkedNode {
+    fn next_mut(&mut self) -> &mut *mut Self;
+}
+
+impl LinkedNode for InputEvent {
+    #[inline]
+    fn next_mut(&mut self) -> &mut *mut Self {
+        &mut self.next
+    }
+}
+
+/// Remove `blocked` from the list. Returns the new head. (null if all are removed)
+///
+/// # Safety
+/// `head` must be null or the head of a valid event list.
+unsafe fn unlink<T: LinkedNode>(head: *mut T, blocked: &[*mut T]) -> *mut T {
+    let mut new_head = head;
+    let mut prev: *mut T = ptr::null_mut();
+    let mut current = head;
+
+    while let Some(node) = unsafe { current.as_mut() } {
+        let next = *node.next_mut();
+        if blocked.contains(&current) {
+            match unsafe { prev.as_mut() } {
+                Some(prev) => *prev.next_mut() = next,
+                None => new_head = next,
+            }
+        } else {
+            prev = current;
+        }
+        current = next;
+    }
+    new_head
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub enum InputHookError {
+    #[snafu(transparent)]
+    HookError { source: HookError },
+
+    /// INPUT_ORIGINAL_FN is already set.
+    FailedToSetInputOriginalFn,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[derive(Debug)]
+    struct Node {
+        id: usize,
+        next: *mut Node,
+    }
+
+    impl LinkedNode for Node {
+        fn next_mut(&mut self) -> &mut *mut Self {
+            &mut self.next
+        }
+    }
+
+    /// Links `nodes` in order, unlinks the nodes at `blocked`, and returns the ids left in the list.
+    fn unlink_ids(len: usize, blocked: &[usize]) -> Vec<usize> {
+        let mut nodes: Vec<Node> = (0..len)
+            .map(|id| Node {
+                id,
+                next: ptr::null_mut(),
+            })
+            .collect();
+        // All accesses go through pointers of the same base, as the game's list does.
+        let base = nodes.as_mut_ptr();
+        let ptrs: Vec<*mut Node> = (0..len).map(|index| unsafe { base.add(index) }).collect();
+        for pair in ptrs.windows(2) {
+            unsafe { (*pair[0]).next = pair[1] };
+        }
+        let blocked: Vec<*mut Node> = blocked.iter().map(|&index| ptrs[index]).collect();
+
+        let head = ptrs.first().copied().unwrap_or(ptr::null_mut());
+        let mut current = unsafe { unlink(head, &blocked) };
+
+        let mut ids = Vec::new();
+        while let Some(node) = unsafe { current.as_ref() } {
+            ids.push(node.id);
+            current = node.next;
+        }
+        ids
+    }
+
+    #[test]
+    fn unlink_nodes() {
+        assert_eq!(unlink_ids(4, &[]), [0, 1, 2, 3]);
+        assert_eq!(unlink_ids(4, &[0]), [1, 2, 3]);
+        assert_eq!(unlink_ids(4, &[0, 1]), [2, 3]);
+        assert_eq!(unlink_ids(4, &[2]), [0, 1, 3]);
+        assert_eq!(unlink_ids(4, &[1, 2]), [0, 3]);
+        assert_eq!(unlink_ids(4, &[3]), [0, 1, 2]);
+        assert_eq!(unlink_ids(4, &[0, 3]), [1, 2]);
+        assert_eq!(unlink_ids(4, &[3, 1]), [0, 2]);
+    }
+
+    #[test]
+    fn unlink_all_nodes() {
+        assert_eq!(unlink_ids(4, &[0, 1, 2, 3]), Vec::<usize>::new());
+        assert_eq!(unlink_ids(1, &[0]), Vec::<usize>::new());
+        assert_eq!(unlink_ids(0, &[]), Vec::<usize>::new());
+    }
+}
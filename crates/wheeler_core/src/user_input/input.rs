@@ -1,4 +1,10 @@
-use core::ffi::CStr;
+//! Input events of the game to Wheeler.
+//!
+//! [`process`] decides what to do with each event without touching the game, and
+//! [`process_and_filter`] is the thin adapter from the game's `InputEvent` list.
+use core::ptr;
+use std::ffi::CString;
+use std::sync::Mutex;
 
 use commonlibsse_ng::re::BSWin32GamepadDevice::{Key, Key_CEnum};
 use commonlibsse_ng::re::ControlMap::ControlMap;
@@ -6,11 +12,95 @@ use commonlibsse_ng::re::InputDevices::INPUT_DEVICE_SE;
 use commonlibsse_ng::re::InputEvent::{Event, InputEvent};
 use commonlibsse_ng::re::UserEvents::{INPUT_CONTEXT_ID, INPUT_CONTEXT_ID_SE};
 
-use super::controls::KeyId;
+use super::controls::{self, KeyEvent, KeyId, KeySet, KeyState};
+use crate::config::WhileOpenConfig;
+use crate::wheeler::{Wheeler, search};
+
+/// `idCode` of mouse wheel button events.
+const MOUSE_WHEEL_UP: u32 = 8;
+const MOUSE_WHEEL_DOWN: u32 = 9;
+
+/// Scan codes of the keys to edit the search query.
+const KEY_ESCAPE: u32 = 0x01;
+const KEY_BACKSPACE: u32 = 0x0E;
+const KEY_ENTER: u32 = 0x1C;
+const KEY_NUMPAD_ENTER: u32 = 0x9C;
+
+/// Gameplay user event of the left thumbstick. Thumbsticks have no id code to look it up.
+const LEFT_STICK_USER_EVENT: &str = "Move";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Keyboard,
+    Mouse,
+    Gamepad,
+    VirtualKeyboard,
+}
+
+/// Game independent copy of an input event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    Button(ButtonInput),
+    /// Raw mouse move counts.
+    MouseMove {
+        x: f32,
+        y: f32,
+    },
+    /// `-1.0..=1.0`, y is up.
+    Thumbstick {
+        is_right: bool,
+        x: f32,
+        y: f32,
+    },
+    /// A typed character, after the keyboard layout.
+    Char(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonInput {
+    pub device: Device,
+    /// Scan code, mouse button or gamepad button mask of the device.
+    pub id_code: u32,
+    /// `0.0` if released. Analog triggers give partial values.
+    pub value: f32,
+    /// Seconds since pressed. `0.0` on the first frame.
+    pub held_secs: f32,
+}
+
+impl ButtonInput {
+    #[inline]
+    pub fn is_down(&self) -> bool {
+        self.value != 0.0 && self.held_secs == 0.0
+    }
+
+    #[inline]
+    pub fn is_up(&self) -> bool {
+        self.value == 0.0 && self.held_secs != 0.0
+    }
+
+    /// Key of the bindings, or `None` for unknown buttons.
+    pub fn key_id(&self) -> Option<KeyId> {
+        match self.device {
+            Device::Keyboard => Some(KeyId(self.id_code)),
+            Device::Mouse => Some(KeyId(KeyId::MOUSE_OFFSET + self.id_code)),
+            Device::Gamepad => {
+                let index = Key_CEnum(self.id_code)
+                    .to_enum()
+                    .and_then(get_gamepad_index);
+                #[cfg(feature = "tracing")]
+                if index.is_none() {
+                    tracing::error!("Not found gamepad index of {:#x}", self.id_code);
+                }
+                index.map(|index| KeyId(KeyId::GAMEPAD_OFFSET + index))
+            }
+            Device::VirtualKeyboard => None,
+        }
+    }
+}
 
 #[inline]
 const fn get_gamepad_index(key: Key) -> Option<u32> {
-    let index = match key {
+    Some(match key {
         Key::Up => 0,
         Key::Down => 1,
         Key::Left => 2,
@@ -29,119 +119,814 @@ const fn get_gamepad_index(key: Key) -> Option<u32> {
         Key::RightTrigger => 15,
 
         Key::LeftStick | Key::RightStick => return None,
+    })
+}
+
+/// State that [`process`] reads. The game implementation is [`GameEnvironment`].
+pub(crate) trait Environment {
+    fn is_wheeler_open(&self) -> bool;
+
+    /// Keys held down, including the key of the event being processed.
+    fn held_keys(&self) -> KeySet;
+
+    /// Is `key` bound in the active contexts with the held modifiers?
+    fn is_key_bound(&self, key: KeyId) -> bool;
+
+    /// Gameplay user event of the button. (e.g. `Inventory`)
+    fn user_event_name(&self, device: Device, id_code: u32) -> Option<CString>;
+
+    /// Is the settings window waiting for a key to bind?
+    fn is_capturing(&self) -> bool;
+
+    /// Is the wheel search taking the typed characters?
+    fn is_searching(&self) -> bool;
+
+    /// Game events to let through while the wheel is open.
+    fn while_open(&self) -> &WhileOpenConfig;
+}
+
+/// What to do with an event.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Outcome {
+    /// Let the game see the event.
+    pub pass: bool,
+    pub commands: Vec<Command>,
+}
+
+impl Outcome {
+    #[inline]
+    const fn pass() -> Self {
+        Self {
+            pass: true,
+            commands: Vec::new(),
+        }
+    }
+
+    #[inline]
+    fn block(command: Command) -> Self {
+        Self {
+            pass: false,
+            commands: vec![command],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Command {
+    /// Run the bindings of the key.
+    Dispatch(KeyEvent),
+    /// Give the key to the rebinding capture.
+    Capture {
+        event: KeyEvent,
+        /// Gameplay user event of the key, to warn the conflict.
+        user_event: Option<String>,
+    },
+    NextItem,
+    PrevItem,
+    MoveCursorMouse {
+        x: f32,
+        y: f32,
+    },
+    MoveCursorGamepad {
+        x: f32,
+        y: f32,
+    },
+    SearchChar(char),
+    SearchBackspace,
+    /// Equip the top match of the search.
+    SearchEquip,
+    SearchCancel,
+}
+
+pub(crate) fn process(input: &Input, env: &impl Environment) -> Outcome {
+    let is_wheeler_open = env.is_wheeler_open();
+
+    match *input {
+        Input::Button(button) => process_button(&button, is_wheeler_open, env),
+        Input::MouseMove { x, y } if is_wheeler_open => {
+            Outcome::block(Command::MoveCursorMouse { x, y })
+        }
+        // The right stick moves the cursor, so the camera must not.
+        Input::Thumbstick {
+            is_right: true,
+            x,
+            y,
+        } if is_wheeler_open => Outcome::block(Command::MoveCursorGamepad { x, y }),
+        Input::Thumbstick {
+            is_right: false, ..
+        } if is_wheeler_open && !env.while_open().is_allowed(LEFT_STICK_USER_EVENT) => Outcome {
+            pass: false,
+            commands: Vec::new(),
+        },
+        Input::Char(c) if is_wheeler_open && env.is_searching() => {
+            Outcome::block(Command::SearchChar(c))
+        }
+        _ => Outcome::pass(),
+    }
+}
+
+fn process_button(button: &ButtonInput, is_wheeler_open: bool, env: &impl Environment) -> Outcome {
+    if env.is_capturing() {
+        return capture_button(button, env);
+    }
+    if is_wheeler_open && env.is_searching() && button.device == Device::Keyboard {
+        return search_button(button);
+    }
+
+    let mut outcome = Outcome::pass();
+
+    if is_wheeler_open && button.device == Device::Mouse && button.is_down() {
+        match button.id_code {
+            MOUSE_WHEEL_UP => outcome.commands.push(Command::PrevItem),
+            MOUSE_WHEEL_DOWN => outcome.commands.push(Command::NextItem),
+            _ => {}
+        }
+    }
+
+    let Some(key) = button.key_id() else {
+        return outcome;
     };
+    let is_key_bound = env.is_key_bound(key);
 
-    const GAMEPAD_OFFSET: u32 = 266;
-    Some(index + GAMEPAD_OFFSET)
+    if is_wheeler_open {
+        outcome.pass = !is_key_bound
+            && env
+                .user_event_name(button.device, button.id_code)
+                .is_some_and(|name| {
+                    name.to_str()
+                        .is_ok_and(|name| env.while_open().is_allowed(name))
+                });
+    }
+
+    if is_key_bound {
+        if let Some(event) = key_event(button, key, env) {
+            outcome.commands.push(Command::Dispatch(event));
+        }
+    }
+
+    outcome
 }
 
-const EVENTS_TO_FILTER_WHEN_WHEELER_ACTIVE: [&CStr; 11] = [
-    c"Favorites",
-    c"Inventory",
-    c"Stats",
-    c"Map",
-    c"Tween Menu",
-    c"Quick Inventory",
-    c"Quick Magic",
-    c"Quick Stats",
-    c"Quick Map",
-    c"Wait",
-    c"Journal",
-];
+/// Every button is blocked while capturing, so that the key does nothing else.
+fn capture_button(button: &ButtonInput, env: &impl Environment) -> Outcome {
+    let mut outcome = Outcome {
+        pass: false,
+        commands: Vec::new(),
+    };
+    if let Some(key) = button.key_id() {
+        if let Some(event) = key_event(button, key, env) {
+            if event.state != KeyState::Held {
+                let user_event = env
+                    .user_event_name(button.device, button.id_code)
+                    .map(|name| name.to_string_lossy().into_owned());
+                outcome
+                    .commands
+                    .push(Command::Capture { event, user_event });
+            }
+        }
+    }
+    outcome
+}
 
-/// # Safety
-pub unsafe fn process_and_filter(event: *mut *mut InputEvent) {
-    let Some(event) = (unsafe { event.as_ref().and_then(|event| event.as_ref()) }) else {
-        return;
+/// Keyboard buttons are blocked while searching, so that typing does nothing else.
+/// The typed characters come as [`Input::Char`].
+fn search_button(button: &ButtonInput) -> Outcome {
+    let command = match button.id_code {
+        _ if !button.is_down() => None,
+        KEY_ENTER | KEY_NUMPAD_ENTER => Some(Command::SearchEquip),
+        KEY_ESCAPE => Some(Command::SearchCancel),
+        KEY_BACKSPACE => Some(Command::SearchBackspace),
+        _ => None,
+    };
+    Outcome {
+        pass: false,
+        commands: command.into_iter().collect(),
+    }
+}
+
+/// `None` if the button is not pressed. (e.g. a released trigger)
+fn key_event(button: &ButtonInput, key: KeyId, env: &impl Environment) -> Option<KeyEvent> {
+    let state = match (button.is_down(), button.is_up()) {
+        (true, _) => KeyState::Down,
+        (_, true) => KeyState::Up,
+        _ if button.value != 0.0 => KeyState::Held,
+        _ => return None,
     };
-    let is_wheeler_open = false; // TODO: crate::wheeler::is_wheeler_open();
+    let mut held = env.held_keys();
+    held.remove(key);
+    Some(KeyEvent {
+        key,
+        device: button.device,
+        state,
+        held_secs: button.held_secs,
+        held,
+    })
+}
+
+/// Keys held down now. Updated before [`process`] sees the event.
+static HELD_KEYS: Mutex<KeySet> = Mutex::new(KeySet::new());
 
-    while let Some(event) = unsafe { event.iter().next() } {
-        let mut should_dispatch = true;
+/// Process the linked list of input events of a frame.
+///
+/// Returns the events the game should not see. [`super::hook`] unlinks them.
+///
+/// # Safety
+/// `event` must be null or point to the head of a valid event list.
+pub unsafe fn process_and_filter(event: *mut *mut InputEvent) -> Vec<*mut InputEvent> {
+    controls::tick();
+
+    let mut blocked = Vec::new();
+    let Some(head) = (unsafe { event.as_ref().and_then(|event| event.as_ref()) }) else {
+        return blocked;
+    };
 
-        let Some(event) = event.cast_to_event() else {
+    let mut env = GameEnvironment {
+        held: KeySet::new(),
+        while_open: crate::config::get().while_open.clone(),
+    };
+    for event in unsafe { head.iter() } {
+        let Some(input) = to_input(event) else {
             continue;
         };
 
-        match event {
-            Event::Button(button_event) => {
-                let mut input = button_event.__base.idCode;
-                let mut is_gamepad = false;
-
-                let Some(device) = button_event.__base.__base.device.as_se() else {
-                    continue;
-                };
-
-                match device {
-                    INPUT_DEVICE_SE::Keyboard => {
-                        break;
-                    }
-                    INPUT_DEVICE_SE::Mouse => {
-                        const MOUSE_OFFSET: u32 = 266;
-                        input += MOUSE_OFFSET;
-                        break;
-                    }
-                    INPUT_DEVICE_SE::Gamepad => {
-                        if let Some(key) = Key_CEnum(input).to_enum() {
-                            if let Some(index) = get_gamepad_index(key) {
-                                input = index;
-                            };
-                        } else {
-                            #[cfg(feature = "tracing")]
-                            tracing::error!("Not found gamepad index!")
-                        };
-                        break;
-                    }
-                    INPUT_DEVICE_SE::FlatVirtualKeyboard => {}
-                }
-
-                let is_key_bound = super::controls::is_key_bound(KeyId(input));
-
-                if is_wheeler_open {
-                    if is_key_bound {
-                        should_dispatch = false;
-                    } else if let Some(ctrl_map) = ControlMap::get_singleton() {
-                        const INPUT_CONTEXT: INPUT_CONTEXT_ID =
-                            INPUT_CONTEXT_ID(INPUT_CONTEXT_ID_SE::Gameplay as u32);
-                        let event_name =
-                            ctrl_map.get_user_event_name(input, device.into(), INPUT_CONTEXT);
-
-                        match event_name {
-                            Some(event_name) => {
-                                should_dispatch = EVENTS_TO_FILTER_WHEN_WHEELER_ACTIVE
-                                    .contains(&event_name.as_c_str())
-                            }
-                            None => should_dispatch = false,
-                        };
-                    }
-                }
+        env.held = update_held_keys(&input);
+        let outcome = process(&input, &env);
+        outcome.commands.into_iter().for_each(execute);
+        if !outcome.pass {
+            blocked.push(ptr::from_ref(event).cast_mut());
+        }
+    }
+    blocked
+}
 
-                if is_key_bound {
-                    let is_down = button_event.is_down();
-                    if is_down || button_event.is_up() {
-                        super::controls::dispatch(
-                            KeyId(button_event.__base.__base.device.0),
-                            is_down,
-                            is_gamepad,
-                        );
-                    }
-                }
-            }
-            Event::MouseMove(mouse_move_event) => {
-                if is_wheeler_open {
-                    // TODO: Wheeler::UpdateCursorPosMouse(mouse_move_ptr->x, mouse_move_ptr->y);
-                    should_dispatch = false;
-                }
-            }
-            Event::Char(_) => {}
-            Event::Thumbstick(thumbstick_event) => {
-                if is_wheeler_open && thumbstick_event.is_right() {
-                    let x = thumbstick_event.xValue;
-                    let y = thumbstick_event.yValue;
-                    // TODO: Wheeler::UpdateCursorPosGamepad(x, y);
-                    should_dispatch = false; // block thumbstick right input when wheel is open.
-                }
+fn update_held_keys(input: &Input) -> KeySet {
+    let mut held = HELD_KEYS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Input::Button(button) = input {
+        if let Some(key) = button.key_id() {
+            match button.value != 0.0 {
+                true => held.insert(key),
+                false => held.remove(key),
             }
+        }
+    }
+    *held
+}
+
+fn to_input(event: &InputEvent) -> Option<Input> {
+    Some(match event.cast_to_event()? {
+        Event::Button(button_event) => Input::Button(ButtonInput {
+            device: match button_event.__base.__base.device.as_se()? {
+                INPUT_DEVICE_SE::Keyboard => Device::Keyboard,
+                INPUT_DEVICE_SE::Mouse => Device::Mouse,
+                INPUT_DEVICE_SE::Gamepad => Device::Gamepad,
+                INPUT_DEVICE_SE::FlatVirtualKeyboard => Device::VirtualKeyboard,
+            },
+            id_code: button_event.__base.idCode,
+            value: button_event.value,
+            held_secs: button_event.heldDownSecs,
+        }),
+        Event::MouseMove(mouse_move_event) => Input::MouseMove {
+            x: mouse_move_event.mouseInputX as f32,
+            y: mouse_move_event.mouseInputY as f32,
+        },
+        Event::Thumbstick(thumbstick_event) => Input::Thumbstick {
+            is_right: thumbstick_event.is_right(),
+            x: thumbstick_event.xValue,
+            y: thumbstick_event.yValue,
+        },
+        Event::Char(char_event) => Input::Char(char::from_u32(char_event.keyCode)?),
+    })
+}
+
+fn execute(command: Command) {
+    match command {
+        Command::Dispatch(event) => {
+            controls::dispatch(controls::active_contexts(), &event);
+        }
+        Command::Capture { event, user_event } => super::rebind::on_key(&event, user_event),
+        Command::NextItem => super::actions::next_item(),
+        Command::PrevItem => super::actions::prev_item(),
+        Command::MoveCursorMouse { x, y } => Wheeler::update_cursor_pos_mouse(x, y),
+        Command::MoveCursorGamepad { x, y } => Wheeler::update_cursor_pos_gamepad(x, y),
+        Command::SearchChar(c) => search::push_char(c),
+        Command::SearchBackspace => search::pop_char(),
+        Command::SearchEquip => search::equip_top_match(),
+        Command::SearchCancel => search::cancel(),
+    }
+}
+
+struct GameEnvironment {
+    held: KeySet,
+    /// Copied once per frame. Holding the config lock would block the bindings that write it.
+    while_open: WhileOpenConfig,
+}
+
+impl Environment for GameEnvironment {
+    #[inline]
+    fn is_wheeler_open(&self) -> bool {
+        crate::wheeler::is_wheeler_open()
+    }
+
+    #[inline]
+    fn held_keys(&self) -> KeySet {
+        self.held
+    }
+
+    #[inline]
+    fn is_key_bound(&self, key: KeyId) -> bool {
+        controls::active_contexts()
+            .iter()
+            .any(|context| controls::is_key_bound(*context, key, &self.held))
+    }
+
+    fn user_event_name(&self, device: Device, id_code: u32) -> Option<CString> {
+        const INPUT_CONTEXT: INPUT_CONTEXT_ID =
+            INPUT_CONTEXT_ID(INPUT_CONTEXT_ID_SE::Gameplay as u32);
+
+        let device = match device {
+            Device::Keyboard => INPUT_DEVICE_SE::Keyboard,
+            Device::Mouse => INPUT_DEVICE_SE::Mouse,
+            Device::Gamepad => INPUT_DEVICE_SE::Gamepad,
+            Device::VirtualKeyboard => INPUT_DEVICE_SE::FlatVirtualKeyboard,
+        };
+        let ctrl_map = ControlMap::get_singleton()?;
+        let name = ctrl_map.get_user_event_name(id_code, device.into(), INPUT_CONTEXT)?;
+        Some(name.as_c_str().to_owned())
+    }
+
+    #[inline]
+    fn is_capturing(&self) -> bool {
+        super::rebind::is_capturing()
+    }
+
+    #[inline]
+    fn is_searching(&self) -> bool {
+        search::is_searching()
+    }
+
+    #[inline]
+    fn while_open(&self) -> &WhileOpenConfig {
+        &self.while_open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EventGroup;
+    use core::ffi::CStr;
+    use pretty_assertions::assert_eq;
+
+    const KEY_A: u32 = 0x1E;
+    const KEY_I: u32 = 0x17;
+
+    #[derive(Default)]
+    struct FakeEnvironment {
+        is_wheeler_open: bool,
+        is_capturing: bool,
+        is_searching: bool,
+        held: KeySet,
+        bound: Vec<KeyId>,
+        /// (device, id code, user event)
+        user_events: Vec<(Device, u32, &'static CStr)>,
+        while_open: WhileOpenConfig,
+    }
+
+    impl Environment for FakeEnvironment {
+        fn is_wheeler_open(&self) -> bool {
+            self.is_wheeler_open
+        }
+
+        fn held_keys(&self) -> KeySet {
+            self.held
+        }
+
+        fn is_key_bound(&self, key: KeyId) -> bool {
+            self.bound.contains(&key)
+        }
+
+        fn user_event_name(&self, device: Device, id_code: u32) -> Option<CString> {
+            self.user_events
+                .iter()
+                .find(|(d, id, _)| *d == device && *id == id_code)
+                .map(|(_, _, name)| (*name).to_owned())
+        }
+
+        fn is_capturing(&self) -> bool {
+            self.is_capturing
+        }
+
+        fn is_searching(&self) -> bool {
+            self.is_searching
+        }
+
+        fn while_open(&self) -> &WhileOpenConfig {
+            &self.while_open
+        }
+    }
+
+    const fn down(device: Device, id_code: u32) -> Input {
+        Input::Button(ButtonInput {
+            device,
+            id_code,
+            value: 1.0,
+            held_secs: 0.0,
+        })
+    }
+
+    const fn up(device: Device, id_code: u32) -> Input {
+        Input::Button(ButtonInput {
+            device,
+            id_code,
+            value: 0.0,
+            held_secs: 0.5,
+        })
+    }
+
+    const fn dispatch(key: u32, device: Device, state: KeyState, held_secs: f32) -> Command {
+        Command::Dispatch(KeyEvent {
+            key: KeyId(key),
+            device,
+            state,
+            held_secs,
+            held: KeySet::new(),
+        })
+    }
+
+    #[test]
+    fn key_ids() {
+        let key_id = |device, id_code| {
+            let Input::Button(button) = down(device, id_code) else {
+                unreachable!()
+            };
+            button.key_id()
+        };
+
+        assert_eq!(key_id(Device::Keyboard, KEY_A), Some(KeyId(KEY_A)));
+        assert_eq!(key_id(Device::Mouse, 0), Some(KeyId(256)));
+        assert_eq!(key_id(Device::Mouse, MOUSE_WHEEL_DOWN), Some(KeyId(265)));
+        assert_eq!(key_id(Device::Gamepad, Key::Up as u32), Some(KeyId(266)));
+        assert_eq!(key_id(Device::Gamepad, Key::A as u32), Some(KeyId(276)));
+        assert_eq!(
+            key_id(Device::Gamepad, Key::RightTrigger as u32),
+            Some(KeyId(281))
+        );
+        assert_eq!(key_id(Device::Gamepad, 0xFFFF_FFFF), None);
+        assert_eq!(key_id(Device::VirtualKeyboard, KEY_A), None);
+    }
+
+    #[test]
+    fn dispatch_bound_keyboard_key() {
+        let env = FakeEnvironment {
+            bound: vec![KeyId(KEY_A)],
+            ..Default::default()
+        };
+
+        let outcome = process(&down(Device::Keyboard, KEY_A), &env);
+        assert_eq!(
+            outcome.commands,
+            [dispatch(KEY_A, Device::Keyboard, KeyState::Down, 0.0)]
+        );
+        assert!(outcome.pass);
+
+        let outcome = process(&up(Device::Keyboard, KEY_A), &env);
+        assert_eq!(
+            outcome.commands,
+            [dispatch(KEY_A, Device::Keyboard, KeyState::Up, 0.5)]
+        );
+
+        // Held frames are for long presses.
+        let held = Input::Button(ButtonInput {
+            device: Device::Keyboard,
+            id_code: KEY_A,
+            value: 1.0,
+            held_secs: 0.3,
+        });
+        assert_eq!(
+            process(&held, &env).commands,
+            [dispatch(KEY_A, Device::Keyboard, KeyState::Held, 0.3)]
+        );
+
+        assert_eq!(
+            process(&down(Device::Keyboard, KEY_I), &env),
+            Outcome::pass()
+        );
+    }
+
+    #[test]
+    fn dispatch_mouse_with_offset() {
+        let env = FakeEnvironment {
+            bound: vec![KeyId(KeyId::MOUSE_OFFSET + 1)],
+            ..Default::default()
+        };
+
+        let outcome = process(&down(Device::Mouse, 1), &env);
+        assert_eq!(
+            outcome.commands,
+            [dispatch(257, Device::Mouse, KeyState::Down, 0.0)]
+        );
+    }
+
+    #[test]
+    fn dispatch_gamepad_with_remapped_key() {
+        let env = FakeEnvironment {
+            bound: vec![KeyId(KeyId::GAMEPAD_OFFSET + 3)],
+            ..Default::default()
+        };
+
+        let outcome = process(&down(Device::Gamepad, Key::Right as u32), &env);
+        assert_eq!(
+            outcome.commands,
+            [dispatch(269, Device::Gamepad, KeyState::Down, 0.0)]
+        );
+        // Not the raw mask of the button.
+        let outcome = process(&down(Device::Gamepad, Key::Up as u32), &env);
+        assert!(outcome.commands.is_empty());
+    }
+
+    #[test]
+    fn dispatch_with_held_modifiers() {
+        const KEY_LSHIFT: u32 = 0x2A;
+        let mut held = KeySet::new();
+        held.insert(KeyId(KEY_LSHIFT));
+        held.insert(KeyId(KEY_A));
+        let env = FakeEnvironment {
+            held,
+            bound: vec![KeyId(KEY_A)],
+            ..Default::default()
+        };
+
+        let outcome = process(&down(Device::Keyboard, KEY_A), &env);
+        let [Command::Dispatch(event)] = outcome.commands[..] else {
+            panic!("expected a dispatch: {:?}", outcome.commands);
+        };
+        // The key itself is not its own modifier.
+        assert!(event.held.contains(KeyId(KEY_LSHIFT)));
+        assert!(!event.held.contains(KeyId(KEY_A)));
+    }
+
+    #[test]
+    fn pass_everything_while_closed() {
+        let env = FakeEnvironment::default();
+
+        assert_eq!(
+            process(&down(Device::Keyboard, KEY_I), &env),
+            Outcome::pass()
+        );
+        assert_eq!(
+            process(&Input::MouseMove { x: 3.0, y: 4.0 }, &env),
+            Outcome::pass()
+        );
+        let stick = Input::Thumbstick {
+            is_right: true,
+            x: 0.5,
+            y: 0.5,
+        };
+        assert_eq!(process(&stick, &env), Outcome::pass());
+    }
+
+    #[test]
+    fn filter_while_open() {
+        const KEY_W: u32 = 0x11;
+        const KEY_E: u32 = 0x12;
+        const KEY_Q: u32 = 0x10;
+        const KEY_B: u32 = 0x30;
+        let env = FakeEnvironment {
+            is_wheeler_open: true,
+            bound: vec![KeyId(KEY_A)],
+            user_events: vec![
+                (Device::Keyboard, KEY_A, c"Strafe Left"),
+                (Device::Keyboard, KEY_I, c"Quick Inventory"),
+                (Device::Keyboard, KEY_W, c"Forward"),
+                (Device::Keyboard, KEY_E, c"Activate"),
+                (Device::Keyboard, KEY_Q, c"Shout"),
+            ],
+            ..Default::default()
+        };
+
+        // (key, pass)
+        let table = [
+            (KEY_A, false), // bound to Wheeler, even if a movement
+            (KEY_W, true),  // movement is allowed by default
+            (KEY_Q, false), // attacks are blocked by default
+            (KEY_I, false), // menus are blocked by default
+            (KEY_E, false), // in no group
+            (KEY_B, false), // no user event
+        ];
+        for (key, pass) in table {
+            let outcome = process(&down(Device::Keyboard, key), &env);
+            assert_eq!((key, outcome.pass), (key, pass));
+        }
+
+        // Bound keys are for Wheeler.
+        let outcome = process(&down(Device::Keyboard, KEY_A), &env);
+        assert_eq!(
+            outcome.commands,
+            [dispatch(KEY_A, Device::Keyboard, KeyState::Down, 0.0)]
+        );
+    }
+
+    #[test]
+    fn filter_while_open_by_config() {
+        let env = FakeEnvironment {
+            is_wheeler_open: true,
+            user_events: vec![
+                (Device::Keyboard, 0x11, c"Forward"),
+                (Device::Keyboard, 0x10, c"Shout"),
+                (Device::Keyboard, KEY_I, c"Quick Inventory"),
+                (Device::Keyboard, 0x12, c"Activate"),
+            ],
+            while_open: WhileOpenConfig {
+                movement: EventGroup {
+                    allow: false,
+                    events: vec!["Forward".into(), "Move".into()],
+                },
+                attacks: EventGroup {
+                    allow: true,
+                    events: vec!["Shout".into()],
+                },
+                menus: EventGroup {
+                    allow: true,
+                    events: vec!["Quick Inventory".into(), "Activate".into()],
+                },
+            },
+            ..Default::default()
+        };
+
+        let left_stick = Input::Thumbstick {
+            is_right: false,
+            x: 0.0,
+            y: 1.0,
+        };
+        // (input, pass)
+        let table = [
+            (down(Device::Keyboard, 0x11), false),
+            (left_stick, false),
+            (down(Device::Keyboard, 0x10), true),
+            (down(Device::Keyboard, KEY_I), true),
+            (down(Device::Keyboard, 0x12), true),
+        ];
+        for (input, pass) in table {
+            assert_eq!((input, process(&input, &env).pass), (input, pass));
+        }
+
+        // Closed: the config doesn't matter.
+        let closed = FakeEnvironment {
+            is_wheeler_open: false,
+            ..env
+        };
+        for (input, _) in table {
+            assert!(process(&input, &closed).pass);
+        }
+    }
+
+    #[test]
+    fn capture_blocks_every_button() {
+        let env = FakeEnvironment {
+            is_wheeler_open: true,
+            is_capturing: true,
+            bound: vec![KeyId(KEY_A)],
+            user_events: vec![(Device::Keyboard, KEY_I, c"Quick Inventory")],
+            ..Default::default()
+        };
+
+        // Not dispatched even if bound.
+        let outcome = process(&down(Device::Keyboard, KEY_A), &env);
+        assert!(!outcome.pass);
+        assert!(matches!(
+            outcome.commands[..],
+            [Command::Capture {
+                user_event: None,
+                ..
+            }]
+        ));
+
+        let outcome = process(&up(Device::Keyboard, KEY_I), &env);
+        assert!(!outcome.pass);
+        let [Command::Capture { event, user_event }] = &outcome.commands[..] else {
+            panic!("expected a capture: {:?}", outcome.commands);
         };
+        assert_eq!(event.state, KeyState::Up);
+        assert_eq!(user_event.as_deref(), Some("Quick Inventory"));
+    }
+
+    #[test]
+    fn move_cursor_while_open() {
+        let env = FakeEnvironment {
+            is_wheeler_open: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            process(&Input::MouseMove { x: 3.0, y: 4.0 }, &env),
+            Outcome::block(Command::MoveCursorMouse { x: 3.0, y: 4.0 })
+        );
+
+        let right = Input::Thumbstick {
+            is_right: true,
+            x: 0.5,
+            y: -0.5,
+        };
+        assert_eq!(
+            process(&right, &env),
+            Outcome::block(Command::MoveCursorGamepad { x: 0.5, y: -0.5 })
+        );
+
+        let left = Input::Thumbstick {
+            is_right: false,
+            x: 0.5,
+            y: -0.5,
+        };
+        assert_eq!(process(&left, &env), Outcome::pass());
+    }
+
+    #[test]
+    fn mouse_wheel_cycles_items_while_open() {
+        let env = FakeEnvironment {
+            is_wheeler_open: true,
+            ..Default::default()
+        };
+
+        let outcome = process(&down(Device::Mouse, MOUSE_WHEEL_UP), &env);
+        assert_eq!(outcome.commands, [Command::PrevItem]);
+        let outcome = process(&down(Device::Mouse, MOUSE_WHEEL_DOWN), &env);
+        assert_eq!(outcome.commands, [Command::NextItem]);
+
+        let closed = FakeEnvironment::default();
+        let outcome = process(&down(Device::Mouse, MOUSE_WHEEL_UP), &closed);
+        assert!(outcome.commands.is_empty());
+    }
+
+    #[test]
+    fn typed_chars_go_to_search() {
+        let searching = FakeEnvironment {
+            is_wheeler_open: true,
+            is_searching: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            process(&Input::Char('é'), &searching),
+            Outcome::block(Command::SearchChar('é'))
+        );
+
+        // Text fields of the game get them otherwise.
+        let not_searching = FakeEnvironment {
+            is_wheeler_open: true,
+            ..Default::default()
+        };
+        assert_eq!(process(&Input::Char('a'), &not_searching), Outcome::pass());
+        let closed = FakeEnvironment {
+            is_searching: true,
+            ..Default::default()
+        };
+        assert_eq!(process(&Input::Char('a'), &closed), Outcome::pass());
+    }
+
+    #[test]
+    fn search_blocks_keyboard() {
+        let env = FakeEnvironment {
+            is_wheeler_open: true,
+            is_searching: true,
+            bound: vec![KeyId(KEY_A), KeyId(256)],
+            user_events: vec![(Device::Keyboard, 0x11, c"Forward")],
+            ..Default::default()
+        };
+        let blocked = |commands: Vec<Command>| Outcome {
+            pass: false,
+            commands,
+        };
+
+        // (input, outcome)
+        let table = [
+            (down(Device::Keyboard, KEY_A), blocked(vec![])),
+            (down(Device::Keyboard, 0x11), blocked(vec![])),
+            (
+                down(Device::Keyboard, KEY_ENTER),
+                blocked(vec![Command::SearchEquip]),
+            ),
+            (
+                down(Device::Keyboard, KEY_NUMPAD_ENTER),
+                blocked(vec![Command::SearchEquip]),
+            ),
+            (up(Device::Keyboard, KEY_ENTER), blocked(vec![])),
+            (
+                down(Device::Keyboard, KEY_ESCAPE),
+                blocked(vec![Command::SearchCancel]),
+            ),
+            (
+                down(Device::Keyboard, KEY_BACKSPACE),
+                blocked(vec![Command::SearchBackspace]),
+            ),
+            // The mouse still works.
+            (
+                down(Device::Mouse, 0),
+                blocked(vec![dispatch(256, Device::Mouse, KeyState::Down, 0.0)]),
+            ),
+        ];
+        for (input, outcome) in table {
+            assert_eq!(process(&input, &env), outcome, "{input:?}");
+        }
     }
 }
@@ -0,0 +1,346 @@
+//! Names of [`KeyId`]s. (e.g. `LShift`, `Mouse4`, `Gamepad LT`)
+//!
+//! Parsing ignores case, spaces and underscores, and also takes the number. (e.g. `42`, `0x2A`)
+//! Single digits are the number keys, not ids.
+use core::fmt;
+use core::str::FromStr;
+
+use super::controls::{Chord, KeyId};
+
+impl KeyId {
+    /// Mouse buttons are `MOUSE_OFFSET + idCode`.
+    pub const MOUSE_OFFSET: u32 = 256;
+    /// Gamepad buttons are `GAMEPAD_OFFSET + index`. (See `get_gamepad_index` of `input.rs`)
+    pub const GAMEPAD_OFFSET: u32 = 266;
+
+    /// `None` if the key has no name.
+    pub fn name(self) -> Option<&'static str> {
+        let id = self.0;
+        match id {
+            ..Self::MOUSE_OFFSET => KEYBOARD_NAMES
+                .iter()
+                .find(|(code, _)| *code == id)
+                .map(|(_, name)| *name),
+            Self::MOUSE_OFFSET..Self::GAMEPAD_OFFSET => {
+                MOUSE_NAMES.get((id - Self::MOUSE_OFFSET) as usize).copied()
+            }
+            _ => GAMEPAD_NAMES
+                .get((id - Self::GAMEPAD_OFFSET) as usize)
+                .copied(),
+        }
+    }
+
+    /// Key of `name`. See the module doc for the accepted forms.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized = normalize(name);
+
+        let named = KEYBOARD_NAMES
+            .iter()
+            .copied()
+            .chain(enumerate_from(Self::MOUSE_OFFSET, &MOUSE_NAMES))
+            .chain(enumerate_from(Self::GAMEPAD_OFFSET, &GAMEPAD_NAMES))
+            .find(|(_, name)| normalize(name) == normalized)
+            .map(|(id, _)| Self(id));
+
+        named.or_else(|| {
+            let id = match normalized.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => normalized.parse().ok()?,
+            };
+            Some(Self(id))
+        })
+    }
+}
+
+fn enumerate_from(
+    offset: u32,
+    names: &'static [&'static str],
+) -> impl Iterator<Item = (u32, &'static str)> {
+    (offset..).zip(names.iter().copied())
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
+/// `LShift+Q`
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in self.modifiers() {
+            write!(f, "{modifier}+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for KeyId {
+    type Err = UnknownKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| UnknownKeyError {
+            name: s.to_string(),
+        })
+    }
+}
+
+/// Unknown key name: `{name}`
+#[derive(Debug, snafu::Snafu)]
+pub struct UnknownKeyError {
+    name: String,
+}
+
+/// Written as the name if it has one.
+impl serde::Serialize for KeyId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.name() {
+            Some(name) => serializer.serialize_str(name),
+            None => serializer.serialize_u32(self.0),
+        }
+    }
+}
+
+/// Read from the name or the number.
+impl<'de> serde::Deserialize<'de> for KeyId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Id(u32),
+            Name(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Id(id) => Ok(Self(id)),
+            Repr::Name(name) => name.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// DirectInput scan codes.
+const KEYBOARD_NAMES: [(u32, &str); 144] = [
+    (0x01, "Escape"),
+    (0x02, "1"),
+    (0x03, "2"),
+    (0x04, "3"),
+    (0x05, "4"),
+    (0x06, "5"),
+    (0x07, "6"),
+    (0x08, "7"),
+    (0x09, "8"),
+    (0x0A, "9"),
+    (0x0B, "0"),
+    (0x0C, "Minus"),
+    (0x0D, "Equals"),
+    (0x0E, "Backspace"),
+    (0x0F, "Tab"),
+    (0x10, "Q"),
+    (0x11, "W"),
+    (0x12, "E"),
+    (0x13, "R"),
+    (0x14, "T"),
+    (0x15, "Y"),
+    (0x16, "U"),
+    (0x17, "I"),
+    (0x18, "O"),
+    (0x19, "P"),
+    (0x1A, "LBracket"),
+    (0x1B, "RBracket"),
+    (0x1C, "Enter"),
+    (0x1D, "LCtrl"),
+    (0x1E, "A"),
+    (0x1F, "S"),
+    (0x20, "D"),
+    (0x21, "F"),
+    (0x22, "G"),
+    (0x23, "H"),
+    (0x24, "J"),
+    (0x25, "K"),
+    (0x26, "L"),
+    (0x27, "Semicolon"),
+    (0x28, "Apostrophe"),
+    (0x29, "Grave"),
+    (0x2A, "LShift"),
+    (0x2B, "Backslash"),
+    (0x2C, "Z"),
+    (0x2D, "X"),
+    (0x2E, "C"),
+    (0x2F, "V"),
+    (0x30, "B"),
+    (0x31, "N"),
+    (0x32, "M"),
+    (0x33, "Comma"),
+    (0x34, "Period"),
+    (0x35, "Slash"),
+    (0x36, "RShift"),
+    (0x37, "Numpad *"),
+    (0x38, "LAlt"),
+    (0x39, "Space"),
+    (0x3A, "CapsLock"),
+    (0x3B, "F1"),
+    (0x3C, "F2"),
+    (0x3D, "F3"),
+    (0x3E, "F4"),
+    (0x3F, "F5"),
+    (0x40, "F6"),
+    (0x41, "F7"),
+    (0x42, "F8"),
+    (0x43, "F9"),
+    (0x44, "F10"),
+    (0x45, "NumLock"),
+    (0x46, "ScrollLock"),
+    (0x47, "Numpad 7"),
+    (0x48, "Numpad 8"),
+    (0x49, "Numpad 9"),
+    (0x4A, "Numpad -"),
+    (0x4B, "Numpad 4"),
+    (0x4C, "Numpad 5"),
+    (0x4D, "Numpad 6"),
+    (0x4E, "Numpad +"),
+    (0x4F, "Numpad 1"),
+    (0x50, "Numpad 2"),
+    (0x51, "Numpad 3"),
+    (0x52, "Numpad 0"),
+    (0x53, "Numpad ."),
+    (0x56, "OEM102"),
+    (0x57, "F11"),
+    (0x58, "F12"),
+    (0x64, "F13"),
+    (0x65, "F14"),
+    (0x66, "F15"),
+    (0x70, "Kana"),
+    (0x73, "AbntC1"),
+    (0x79, "Convert"),
+    (0x7B, "NoConvert"),
+    (0x7D, "Yen"),
+    (0x7E, "AbntC2"),
+    (0x8D, "Numpad ="),
+    (0x90, "PrevTrack"),
+    (0x91, "At"),
+    (0x92, "Colon"),
+    (0x93, "Underline"),
+    (0x94, "Kanji"),
+    (0x95, "Stop"),
+    (0x96, "Ax"),
+    (0x97, "Unlabeled"),
+    (0x99, "NextTrack"),
+    (0x9C, "Numpad Enter"),
+    (0x9D, "RCtrl"),
+    (0xA0, "Mute"),
+    (0xA1, "Calculator"),
+    (0xA2, "PlayPause"),
+    (0xA4, "MediaStop"),
+    (0xAE, "VolumeDown"),
+    (0xB0, "VolumeUp"),
+    (0xB2, "WebHome"),
+    (0xB3, "Numpad ,"),
+    (0xB5, "Numpad /"),
+    (0xB7, "PrintScreen"),
+    (0xB8, "RAlt"),
+    (0xC5, "Pause"),
+    (0xC7, "Home"),
+    (0xC8, "Up"),
+    (0xC9, "PageUp"),
+    (0xCB, "Left"),
+    (0xCD, "Right"),
+    (0xCF, "End"),
+    (0xD0, "Down"),
+    (0xD1, "PageDown"),
+    (0xD2, "Insert"),
+    (0xD3, "Delete"),
+    (0xDB, "LWin"),
+    (0xDC, "RWin"),
+    (0xDD, "Apps"),
+    (0xDE, "Power"),
+    (0xDF, "Sleep"),
+    (0xE3, "Wake"),
+    (0xE5, "WebSearch"),
+    (0xE6, "WebFavorites"),
+    (0xE7, "WebRefresh"),
+    (0xE8, "WebStop"),
+    (0xE9, "WebForward"),
+    (0xEA, "WebBack"),
+    (0xEB, "MyComputer"),
+    (0xEC, "Mail"),
+    (0xED, "MediaSelect"),
+];
+
+/// By `idCode`.
+const MOUSE_NAMES: [&str; 10] = [
+    "Mouse1", // Left
+    "Mouse2", // Right
+    "Mouse3", // Middle
+    "Mouse4",
+    "Mouse5",
+    "Mouse6",
+    "Mouse7",
+    "Mouse8",
+    "Mouse Wheel Up",
+    "Mouse Wheel Down",
+];
+
+/// By the index of `get_gamepad_index` in `input.rs`.
+const GAMEPAD_NAMES: [&str; 16] = [
+    "Gamepad DPad Up",
+    "Gamepad DPad Down",
+    "Gamepad DPad Left",
+    "Gamepad DPad Right",
+    "Gamepad Start",
+    "Gamepad Back",
+    "Gamepad LS",
+    "Gamepad RS",
+    "Gamepad LB",
+    "Gamepad RB",
+    "Gamepad A",
+    "Gamepad B",
+    "Gamepad X",
+    "Gamepad Y",
+    "Gamepad LT",
+    "Gamepad RT",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn names() {
+        assert_eq!(KeyId(0x2A).name(), Some("LShift"));
+        assert_eq!(KeyId(259).name(), Some("Mouse4"));
+        assert_eq!(KeyId(280).name(), Some("Gamepad LT"));
+        assert_eq!(KeyId(266).name(), Some("Gamepad DPad Up"));
+        assert_eq!(KeyId(0x54).name(), None);
+        assert_eq!(KeyId(282).name(), None);
+        assert_eq!(KeyId(0x54).to_string(), "0x54");
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(KeyId::from_name("lshift"), Some(KeyId(0x2A)));
+        assert_eq!(KeyId::from_name("gamepad_dpad_up"), Some(KeyId(266)));
+        assert_eq!(KeyId::from_name("NumpadEnter"), Some(KeyId(0x9C)));
+        assert_eq!(KeyId::from_name("42"), Some(KeyId(42)));
+        assert_eq!(KeyId::from_name("0x2A"), Some(KeyId(0x2A)));
+        assert_eq!(KeyId::from_name("Hyper"), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        for id in 0..KeyId::GAMEPAD_OFFSET + GAMEPAD_NAMES.len() as u32 {
+            let key = KeyId(id);
+            assert_eq!(key.to_string().parse::<KeyId>().ok(), Some(key));
+        }
+    }
+}
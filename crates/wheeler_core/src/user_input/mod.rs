@@ -1,2 +1,7 @@
+pub mod actions;
 pub mod controls;
+mod gesture;
+pub mod hook;
 pub mod input;
+mod key_name;
+pub mod rebind;
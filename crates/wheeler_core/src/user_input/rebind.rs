@@ -0,0 +1,324 @@
+//! "Press a key to bind" capture of the settings window.
+//!
+//! While capturing, [`super::input`] blocks all buttons and sends them here instead of the bindings.
+use std::ffi::CString;
+use std::sync::{Mutex, MutexGuard};
+
+use commonlibsse_ng_re_ext::re_ext::hud::debug_notification;
+
+use super::controls::{Chord, KeyEvent, KeySet, KeyState};
+use super::input::Device;
+use crate::config::{ConfigError, ControlsConfig, DeviceSlot};
+
+/// Cancels the capture.
+const KEY_ESCAPE: u32 = 0x01;
+
+static CAPTURE: Mutex<Option<Capture>> = Mutex::new(None);
+
+#[derive(Debug, Clone)]
+pub struct Capture {
+    /// One of [`ControlsConfig::ACTIONS`]
+    pub action: &'static str,
+    pub slot: DeviceSlot,
+    /// Pressed since the capture started. Releasing one of them completes the chord.
+    pressed: KeySet,
+    /// The chord waiting for the confirmation because of conflicts.
+    pub captured: Option<Captured>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Captured {
+    pub chord: Chord,
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// Another Wheeler action has the same chord.
+    Action(&'static str),
+    /// A number key of the `index`th entry. (See [`NumberKeysConfig`](crate::config::NumberKeysConfig))
+    NumberKey { index: usize, left_hand: bool },
+    /// The game uses the key for this user event in gameplay. (e.g. `Sprint`)
+    UserEvent(String),
+}
+
+/// Wait for a key of `slot` for `action`. Replaces the running capture.
+pub fn start(action: &'static str, slot: DeviceSlot) {
+    *lock() = Some(Capture {
+        action,
+        slot,
+        pressed: KeySet::new(),
+        captured: None,
+    });
+}
+
+pub fn cancel() {
+    *lock() = None;
+}
+
+/// The running capture, for the settings window.
+pub fn current() -> Option<Capture> {
+    lock().clone()
+}
+
+/// Waiting for a key? (not for the confirmation)
+pub fn is_capturing() -> bool {
+    lock()
+        .as_ref()
+        .is_some_and(|capture| capture.captured.is_none())
+}
+
+/// A button while capturing.
+///
+/// - `user_event`: gameplay user event of the button
+///
+/// The chord is taken on release, so that modifiers can be held first. (e.g. `LShift`, then `Q`)
+pub(crate) fn on_key(event: &KeyEvent, user_event: Option<String>) {
+    let (action, slot, chord) = {
+        let mut capture = lock();
+        if capture
+            .as_ref()
+            .is_none_or(|current| current.captured.is_some())
+        {
+            return;
+        }
+        if event.key.0 == KEY_ESCAPE {
+            *capture = None;
+            return;
+        }
+        let Some(current) = capture.as_mut() else {
+            return;
+        };
+        if !is_slot_device(current.slot, event.device) {
+            return;
+        }
+
+        match event.state {
+            KeyState::Down => {
+                current.pressed.insert(event.key);
+                return;
+            }
+            // Keys pressed before the start are not for this capture.
+            KeyState::Up if current.pressed.contains(event.key) => {}
+            _ => return,
+        }
+        let modifiers = event
+            .held
+            .iter()
+            .filter(|key| current.pressed.contains(*key));
+        (
+            current.action,
+            current.slot,
+            Chord::new(modifiers, event.key),
+        )
+    };
+
+    // Read the config without holding the capture lock.
+    let conflicts = find_conflicts(
+        &crate::config::get().controls,
+        action,
+        slot,
+        &chord,
+        user_event,
+    );
+    let has_conflicts = !conflicts.is_empty();
+    if let Some(current) = lock().as_mut() {
+        current.captured = Some(Captured { chord, conflicts });
+    }
+    if !has_conflicts {
+        apply();
+    }
+}
+
+const fn is_slot_device(slot: DeviceSlot, device: Device) -> bool {
+    match slot {
+        DeviceSlot::Keyboard => matches!(device, Device::Keyboard | Device::Mouse),
+        DeviceSlot::Gamepad => matches!(device, Device::Gamepad),
+    }
+}
+
+fn find_conflicts(
+    controls: &ControlsConfig,
+    action: &str,
+    slot: DeviceSlot,
+    chord: &Chord,
+    user_event: Option<String>,
+) -> Vec<Conflict> {
+    let actions = ControlsConfig::ACTIONS
+        .iter()
+        .copied()
+        .filter(|other| *other != action)
+        .filter(|other| {
+            controls
+                .binding(other)
+                .is_some_and(|binding| binding.chord(slot).as_ref() == Some(chord))
+        })
+        .map(Conflict::Action);
+    let number_key = controls
+        .number_keys
+        .find_entry(chord)
+        .map(|(index, left_hand)| Conflict::NumberKey { index, left_hand });
+    actions
+        .chain(number_key)
+        .chain(user_event.map(Conflict::UserEvent))
+        .collect()
+}
+
+/// Bind the captured chord, even with conflicts, and write the config file.
+pub fn apply() {
+    let mut capture = lock();
+    let Some(Capture {
+        action,
+        slot,
+        captured: Some(Captured { chord, .. }),
+        ..
+    }) = capture.take_if(|current| current.captured.is_some())
+    else {
+        return;
+    };
+    drop(capture);
+    set_binding(action, slot, Some(chord));
+}
+
+/// Unbind `slot` of `action`.
+pub fn clear(action: &'static str, slot: DeviceSlot) {
+    set_binding(action, slot, None);
+}
+
+fn set_binding(action: &str, slot: DeviceSlot, chord: Option<Chord>) {
+    crate::config::update(|config| {
+        if let Some(binding) = config.controls.binding_mut(action) {
+            binding.set_chord(slot, chord);
+        }
+    });
+    super::actions::bind_from_config();
+
+    if let Err(err) = crate::config::save() {
+        report(&err);
+    }
+}
+
+fn report(err: &ConfigError) {
+    #[cfg(feature = "tracing")]
+    tracing::error!("{err}");
+    if let Ok(message) = CString::new(err.to_string()) {
+        debug_notification(&message);
+    }
+}
+
+fn lock() -> MutexGuard<'static, Option<Capture>> {
+    CAPTURE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user_input::controls::KeyId;
+    use pretty_assertions::assert_eq;
+
+    const KEY_1: KeyId = KeyId(0x02);
+    const KEY_0: KeyId = KeyId(0x0B);
+    const L_ALT: KeyId = KeyId(0x38);
+    const L_SHIFT: KeyId = KeyId(0x2A);
+
+    fn conflicts(controls: &ControlsConfig, chord: &Chord) -> Vec<Conflict> {
+        find_conflicts(controls, "next_item", DeviceSlot::Keyboard, chord, None)
+    }
+
+    #[test]
+    fn action_conflicts() {
+        let mut controls = ControlsConfig::default();
+        let chord = Chord::new([L_SHIFT], KeyId(0x10));
+        controls
+            .toggle_wheel
+            .set_chord(DeviceSlot::Keyboard, Some(chord.clone()));
+        controls
+            .search
+            .set_chord(DeviceSlot::Keyboard, Some(chord.clone()));
+        controls
+            .undo
+            .set_chord(DeviceSlot::Gamepad, Some(chord.clone()));
+
+        // The action being bound doesn't conflict with itself, nor the other slot.
+        assert_eq!(
+            find_conflicts(&controls, "search", DeviceSlot::Keyboard, &chord, None),
+            [Conflict::Action("toggle_wheel")]
+        );
+        assert_eq!(
+            conflicts(&controls, &chord),
+            [Conflict::Action("toggle_wheel"), Conflict::Action("search")]
+        );
+        assert_eq!(
+            find_conflicts(&controls, "next_item", DeviceSlot::Gamepad, &chord, None),
+            [Conflict::Action("undo")]
+        );
+
+        // Modifiers must match too.
+        assert_eq!(conflicts(&controls, &KeyId(0x10).into()), []);
+        assert_eq!(
+            conflicts(&controls, &Chord::new([L_SHIFT, L_ALT], KeyId(0x10))),
+            []
+        );
+    }
+
+    #[test]
+    fn user_event_conflict_comes_last() {
+        let mut controls = ControlsConfig::default();
+        controls
+            .search
+            .set_chord(DeviceSlot::Keyboard, Some(KEY_1.into()));
+
+        let conflicts = find_conflicts(
+            &controls,
+            "next_item",
+            DeviceSlot::Keyboard,
+            &KEY_1.into(),
+            Some("Hotkey1".to_string()),
+        );
+        assert_eq!(
+            conflicts,
+            [
+                Conflict::Action("search"),
+                Conflict::NumberKey {
+                    index: 0,
+                    left_hand: false
+                },
+                Conflict::UserEvent("Hotkey1".to_string()),
+            ]
+        );
+
+        let g = KeyId(0x22).into();
+        assert_eq!(
+            find_conflicts(&controls, "toggle_wheel", DeviceSlot::Keyboard, &g, None),
+            []
+        );
+    }
+
+    #[test]
+    fn number_key_conflicts() {
+        let mut controls = ControlsConfig::default();
+        let number_key = |index, left_hand| Conflict::NumberKey { index, left_hand };
+
+        assert_eq!(conflicts(&controls, &KEY_1.into()), [number_key(0, false)]);
+        assert_eq!(conflicts(&controls, &KEY_0.into()), [number_key(9, false)]);
+        assert_eq!(
+            conflicts(&controls, &Chord::new([L_ALT], KEY_0)),
+            [number_key(9, true)]
+        );
+        // Not the left hand modifier
+        assert_eq!(conflicts(&controls, &Chord::new([L_SHIFT], KEY_1)), []);
+        assert_eq!(
+            conflicts(&controls, &Chord::new([L_ALT, L_SHIFT], KEY_1)),
+            []
+        );
+
+        controls.number_keys.left_hand_modifier = None;
+        assert_eq!(conflicts(&controls, &Chord::new([L_ALT], KEY_1)), []);
+        assert_eq!(conflicts(&controls, &KEY_1.into()), [number_key(0, false)]);
+
+        controls.number_keys.enabled = false;
+        assert_eq!(conflicts(&controls, &KEY_1.into()), []);
+    }
+}
@@ -0,0 +1,149 @@
+//! Armor set: swap the whole outfit by one activation.
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng_re_ext::re_ext::inventory::{
+    get_extra_list_unique_id, is_worn, player_inventory_entries,
+};
+
+use super::equip::{EquipError, EquipHand, armor_slot_mask, equip_form, unequip_armor_outside};
+use super::item::{form_name, serde_form_id};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ArmorSet {
+    /// Shown as the title. If empty, the names of the pieces are joined.
+    pub name: String,
+    pub pieces: Vec<ArmorPiece>,
+    /// Unequip worn armor in the slots this set doesn't cover. (e.g. take off the helmet)
+    pub unequip_uncovered: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ArmorPiece {
+    #[serde(with = "serde_form_id")]
+    pub form_id: FormID,
+    /// Points a specific instance. (e.g. an enchanted ring)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_id: Option<u16>,
+}
+
+impl ArmorSet {
+    /// Title of the slot and the tooltip.
+    pub fn display_name(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+        self.pieces
+            .iter()
+            .map(ArmorPiece::name)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// Biped slots of the pieces.
+    ///
+    /// Pieces no longer in the inventory still count, so their slots are left as they are.
+    pub fn slot_mask(&self) -> u32 {
+        self.pieces
+            .iter()
+            .filter_map(ArmorPiece::form)
+            .fold(0, |mask, form| mask | armor_slot_mask(form))
+    }
+
+    /// Equip all pieces. A missing piece does not stop the others.
+    ///
+    /// # Errors
+    /// If any piece failed. (e.g. sold or dropped)
+    pub(crate) fn equip(&self) -> Result<(), ArmorSetError> {
+        let mut failed = Vec::new();
+
+        if self.unequip_uncovered {
+            if let Err(err) = unequip_armor_outside(self.slot_mask()) {
+                failed.push(err);
+            }
+        }
+
+        for piece in &self.pieces {
+            if let Err(err) = equip_form(piece.form_id, piece.unique_id, EquipHand::Right) {
+                failed.push(err);
+            }
+        }
+
+        match failed.is_empty() {
+            true => Ok(()),
+            false => ArmorSetSnafu {
+                name: self.display_name(),
+                failed,
+            }
+            .fail(),
+        }
+    }
+
+    /// Capture the armor and clothing the player wears now.
+    pub(crate) fn from_worn() -> Option<Self> {
+        let pieces: Vec<_> = worn_pieces().map(|(_, piece)| piece).collect();
+
+        match pieces.is_empty() {
+            true => None,
+            false => Some(Self {
+                name: String::new(),
+                pieces,
+                unequip_uncovered: false,
+            }),
+        }
+    }
+}
+
+/// Worn armor and clothing of the player, with their biped slots.
+fn worn_pieces() -> impl Iterator<Item = (u32, ArmorPiece)> {
+    player_inventory_entries().filter_map(|entry| {
+        let form = entry.get_object()?.__base.__base.get_form();
+        let slot_mask = armor_slot_mask(form);
+        if slot_mask == 0 {
+            return None;
+        }
+        let extra_list = entry
+            .extraLists
+            .as_ref()?
+            .iter()
+            .find(|extra_list| is_worn(extra_list))?;
+        let piece = ArmorPiece {
+            form_id: form.formID,
+            unique_id: get_extra_list_unique_id(extra_list),
+        };
+        Some((slot_mask, piece))
+    })
+}
+
+/// The worn piece in any of the biped slots of `slot_mask`.
+pub(super) fn find_worn_piece(slot_mask: u32) -> Option<ArmorPiece> {
+    worn_pieces()
+        .find(|(mask, _)| mask & slot_mask != 0)
+        .map(|(_, piece)| piece)
+}
+
+impl ArmorPiece {
+    pub fn form(&self) -> Option<&'static TESForm> {
+        TESForm::lookup_by_id(self.form_id).map(|form| unsafe { form.as_ref() })
+    }
+
+    pub fn name(&self) -> String {
+        self.form().map(form_name).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, snafu::Snafu)]
+#[snafu(display("Failed to equip `{name}`: {}", describe_failures(failed)))]
+pub struct ArmorSetError {
+    name: String,
+    failed: Vec<EquipError>,
+}
+
+fn describe_failures(failed: &[EquipError]) -> String {
+    failed
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,171 @@
+//! Stack count badge and charge bar on slots.
+//!
+//! Looking up the inventory is not free, so the results are cached until the inventory changes.
+use std::sync::LazyLock;
+
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::re::FormTypes::FormType;
+use commonlibsse_ng::re::TESContainerChangedEvent::TESContainerChangedEvent;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng_re_ext::re_ext::event::add_container_changed_event_sink;
+use commonlibsse_ng_re_ext::re_ext::inventory::get_player_item_count;
+use dashmap::DashMap;
+use imgui::sys::ImDrawList;
+
+use super::item::WheelItem;
+use super::stats::enchantment_charge;
+use crate::config::{BadgeConfig, BadgeCorner};
+use crate::rendering::draw::{calc_text_size, draw_rect_filled, draw_text};
+
+// NOTE: Sizes are for 1080p. Multiply the resolution scale.
+const PADDING: f32 = 4.0;
+const CHARGE_BAR_WIDTH: f32 = 72.0;
+const CHARGE_BAR_HEIGHT: f32 = 6.0;
+
+const PLAYER_FORM_ID: FormID = FormID::new(0x14);
+
+/// key: (form id, unique id)
+static BADGES: LazyLock<DashMap<(FormID, Option<u16>), Option<Badge>>> =
+    LazyLock::new(DashMap::new);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Badge {
+    Count(i32),
+    /// `0.0..=1.0`
+    Charge(f32),
+}
+
+/// Invalidate the cache when items go in or out of the player's inventory.
+///
+/// Call this after `DataLoaded`.
+pub fn install_inventory_watcher() -> Option<()> {
+    add_container_changed_event_sink(on_container_changed)
+}
+
+fn on_container_changed(event: &TESContainerChangedEvent) {
+    if event.oldContainer == PLAYER_FORM_ID || event.newContainer == PLAYER_FORM_ID {
+        invalidate(event.baseObj);
+    }
+}
+
+/// Drop the cache of `form_id`.
+pub fn invalidate(form_id: FormID) {
+    BADGES.retain(|(id, _), _| *id != form_id);
+}
+
+/// Drop the whole cache. (e.g. on load, charge is consumed by attacks)
+pub fn invalidate_all() {
+    BADGES.clear();
+}
+
+fn badge(item: &WheelItem) -> Option<Badge> {
+    let WheelItem::Form { form_id, unique_id } = item else {
+        return None;
+    };
+    let key = (*form_id, *unique_id);
+    if let Some(badge) = BADGES.get(&key) {
+        return *badge;
+    }
+
+    let badge = item
+        .form()
+        .and_then(|form| compute(unsafe { form.as_ref() }, *unique_id));
+    BADGES.insert(key, badge);
+    badge
+}
+
+fn compute(form: &TESForm, unique_id: Option<u16>) -> Option<Badge> {
+    match form.get_form_type() {
+        // Potions and poisons are both `AlchemyItem`.
+        FormType::AlchemyItem | FormType::Scroll | FormType::Ammo => {
+            let count = get_player_item_count(form.formID);
+            (count > 0).then_some(Badge::Count(count))
+        }
+        FormType::Weapon => enchantment_charge(form, unique_id).map(Badge::Charge),
+        _ => None,
+    }
+}
+
+/// Draw the badge of `item` on the slot at `slot_center`.
+///
+/// - `slot_size`: scaled size of the slot
+pub(crate) fn draw(
+    draw_list: *mut ImDrawList,
+    item: &WheelItem,
+    slot_center: [f32; 2],
+    slot_size: f32,
+    scale: f32,
+    config: &BadgeConfig,
+) {
+    match badge(item) {
+        Some(Badge::Count(count)) if config.count => {
+            draw_count(draw_list, count, slot_center, slot_size, scale, config);
+        }
+        Some(Badge::Charge(charge)) if config.charge_bar => {
+            draw_charge_bar(draw_list, charge, slot_center, slot_size, scale, config);
+        }
+        _ => {}
+    }
+}
+
+fn draw_count(
+    draw_list: *mut ImDrawList,
+    count: i32,
+    slot_center: [f32; 2],
+    slot_size: f32,
+    scale: f32,
+    config: &BadgeConfig,
+) {
+    let text = count.to_string();
+    let font_size = unsafe { imgui::sys::igGetFontSize() } * config.font_scale * scale;
+    let [text_width, text_height] = calc_text_size(font_size, &text);
+    let padding = PADDING * scale;
+    let size = [text_width + padding * 2.0, text_height + padding];
+
+    let half = slot_size * 0.5;
+    let is_left = matches!(
+        config.corner,
+        BadgeCorner::TopLeft | BadgeCorner::BottomLeft
+    );
+    let is_top = matches!(config.corner, BadgeCorner::TopLeft | BadgeCorner::TopRight);
+    let min = [
+        match is_left {
+            true => slot_center[0] - half,
+            false => slot_center[0] + half - size[0],
+        },
+        match is_top {
+            true => slot_center[1] - half,
+            false => slot_center[1] + half - size[1],
+        },
+    ];
+    let max = [min[0] + size[0], min[1] + size[1]];
+
+    draw_rect_filled(draw_list, min, max, config.background_color, padding);
+    draw_text(
+        draw_list,
+        [min[0] + padding, min[1] + padding * 0.5],
+        font_size,
+        config.text_color,
+        &text,
+    );
+}
+
+fn draw_charge_bar(
+    draw_list: *mut ImDrawList,
+    charge: f32,
+    slot_center: [f32; 2],
+    slot_size: f32,
+    scale: f32,
+    config: &BadgeConfig,
+) {
+    let width = CHARGE_BAR_WIDTH * scale;
+    let height = CHARGE_BAR_HEIGHT * scale;
+    let bottom = slot_center[1] + slot_size * 0.5 - PADDING * scale;
+
+    let min = [slot_center[0] - width * 0.5, bottom - height];
+    let max = [slot_center[0] + width * 0.5, bottom];
+    draw_rect_filled(draw_list, min, max, config.charge_background_color, 0.0);
+
+    let filled = [min[0] + width * charge.clamp(0.0, 1.0), max[1]];
+    draw_rect_filled(draw_list, min, filled, config.charge_color, 0.0);
+}
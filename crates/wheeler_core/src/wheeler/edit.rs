@@ -0,0 +1,315 @@
+//! Edit mode. Build wheels in game from the items selected in the inventory or magic menu.
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use commonlibsse_ng::re::InventoryMenu::InventoryMenu;
+use commonlibsse_ng::re::MagicMenu::MagicMenu;
+use commonlibsse_ng::re::UI::UI;
+use commonlibsse_ng_re_ext::re_ext::inventory::{
+    get_entry_unique_id, get_selected_item_in_inventory,
+};
+use commonlibsse_ng_re_ext::re_ext::magic::get_selected_item_in_magic_menu;
+
+use super::history::Edit;
+use super::smart;
+use super::{ArmorSet, Loadout, Wheel, WheelEntry, WheelItem, WheelerData, lock};
+
+static EDIT_MODE: AtomicBool = AtomicBool::new(false);
+
+#[inline]
+pub fn is_edit_mode() -> bool {
+    EDIT_MODE.load(Ordering::Acquire)
+}
+
+pub fn toggle_edit_mode() {
+    EDIT_MODE.fetch_xor(true, Ordering::AcqRel);
+}
+
+/// Add the item selected in the inventory or magic menu to the hovered entry.
+pub fn add_selected_item() {
+    if let Some(item) = selected_menu_item() {
+        add_item(item);
+    }
+}
+
+/// Add the current weapons, spells, ammo and shout of the player as a loadout.
+pub fn add_equipped_loadout() {
+    if let Some(loadout) = Loadout::from_equipped() {
+        add_item(WheelItem::Loadout(loadout));
+    }
+}
+
+/// Add the armor and clothing the player wears as an armor set.
+pub fn add_worn_armor_set() {
+    if let Some(armor_set) = ArmorSet::from_worn() {
+        add_item(WheelItem::ArmorSet(armor_set));
+    }
+}
+
+/// Switch [`ArmorSet::unequip_uncovered`] of the hovered armor set.
+pub fn toggle_unequip_uncovered() {
+    let mut data = lock();
+    let wheel = data.active;
+    let Some((entry, index, mut armor_set)) = data.active_wheel().and_then(|active| {
+        let entry = active.hovered_index()?;
+        let hovered = active.entries.get(entry)?;
+        match hovered.current_item()? {
+            WheelItem::ArmorSet(armor_set) => {
+                Some((entry, hovered.selected_index(), armor_set.clone()))
+            }
+            _ => None,
+        }
+    }) else {
+        return;
+    };
+    armor_set.unequip_uncovered = !armor_set.unequip_uncovered;
+
+    // Replace the item, so that the change can be undone.
+    data.execute(Edit::Batch(vec![
+        Edit::RemoveItem {
+            wheel,
+            entry,
+            index,
+        },
+        Edit::InsertItem {
+            wheel,
+            entry,
+            index,
+            item: WheelItem::ArmorSet(armor_set),
+        },
+    ]));
+}
+
+/// Add a smart entry of the first consumable rule matching the item selected in the menu.
+pub fn add_smart_item() {
+    let Some(form) = selected_menu_item().as_ref().and_then(WheelItem::form) else {
+        return;
+    };
+    let rule = smart::find_rule(&crate::config::get().consumables.rules, unsafe {
+        form.as_ref()
+    });
+    if let Some(rule) = rule {
+        add_item(WheelItem::Smart(rule));
+    }
+}
+
+/// Add `item` to the hovered entry. Creates a wheel and an entry if there is none.
+fn add_item(item: WheelItem) {
+    let mut data = lock();
+    let mut edits = Vec::new();
+    if data.wheels.is_empty() {
+        edits.push(Edit::InsertWheel {
+            index: 0,
+            wheel: Wheel::new(),
+        });
+    }
+    let wheel = data.active;
+
+    let target =
+        data.active_wheel()
+            .map_or(Some((0, None)), |active| match active.hovered_index() {
+                Some(index) => active
+                    .entries
+                    .get(index)
+                    .map(|hovered| (index, Some(hovered))),
+                None if active.entries.is_empty() => Some((0, None)),
+                None => None,
+            });
+    let Some((entry, hovered)) = target else {
+        return;
+    };
+
+    let index = match hovered {
+        Some(hovered) if hovered.items.contains(&item) => return,
+        Some(hovered) => hovered.items.len(),
+        None => {
+            edits.push(Edit::InsertEntry {
+                wheel,
+                index: 0,
+                entry: WheelEntry::default(),
+            });
+            0
+        }
+    };
+    edits.push(Edit::InsertItem {
+        wheel,
+        entry,
+        index,
+        item,
+    });
+
+    data.execute(Edit::Batch(edits));
+}
+
+/// Remove the shown item of the hovered entry.
+pub fn remove_hovered_item() {
+    let mut data = lock();
+    let wheel = data.active;
+    let Some((entry, index)) = data.active_wheel().and_then(|active| {
+        let entry = active.hovered_index()?;
+        Some((entry, active.entries.get(entry)?.selected_index()))
+    }) else {
+        return;
+    };
+    data.execute(Edit::RemoveItem {
+        wheel,
+        entry,
+        index,
+    });
+}
+
+/// Insert an empty entry after the hovered one, or at the end.
+pub fn insert_entry() {
+    let mut data = lock();
+    let mut edits = Vec::new();
+    if data.wheels.is_empty() {
+        edits.push(Edit::InsertWheel {
+            index: 0,
+            wheel: Wheel::new(),
+        });
+    }
+    let wheel = data.active;
+    let index = data.active_wheel().map_or(0, |active| {
+        active
+            .hovered_index()
+            .map_or(active.entries.len(), |index| index + 1)
+    });
+    edits.push(Edit::InsertEntry {
+        wheel,
+        index,
+        entry: WheelEntry::default(),
+    });
+
+    data.execute(Edit::Batch(edits));
+}
+
+pub fn remove_hovered_entry() {
+    let mut data = lock();
+    let wheel = data.active;
+    if let Some(index) = data.active_wheel().and_then(Wheel::hovered_index) {
+        data.execute(Edit::RemoveEntry { wheel, index });
+    }
+}
+
+/// Add an empty wheel after the active one and show it.
+pub fn add_wheel() {
+    let mut data = lock();
+    let index = match data.wheels.is_empty() {
+        true => 0,
+        false => data.active + 1,
+    };
+    data.execute(Edit::InsertWheel {
+        index,
+        wheel: Wheel::new(),
+    });
+}
+
+pub fn remove_active_wheel() {
+    let mut data = lock();
+    let index = data.active;
+    data.execute(Edit::RemoveWheel { index });
+}
+
+pub fn undo() {
+    lock().undo();
+}
+
+pub fn redo() {
+    lock().redo();
+}
+
+/// The item under the cursor of the opened inventory or magic menu.
+fn selected_menu_item() -> Option<WheelItem> {
+    let ui = UI::get_singleton()?;
+
+    if let Some(menu) = ui.get_menu::<InventoryMenu>(InventoryMenu::MENU_NAME) {
+        let entry = get_selected_item_in_inventory(menu)?;
+        let object = entry.get_object()?;
+        return Some(WheelItem::Form {
+            form_id: object.__base.__base.get_form().formID,
+            unique_id: get_entry_unique_id(entry),
+        });
+    }
+
+    if let Some(menu) = ui.get_menu::<MagicMenu>(MagicMenu::MENU_NAME) {
+        let form = get_selected_item_in_magic_menu(menu)?;
+        return Some(WheelItem::Form {
+            form_id: unsafe { form.as_ref() }.formID,
+            unique_id: None,
+        });
+    }
+
+    None
+}
+
+/// Mutations of the wheels. Out of range indices are ignored.
+///
+/// Use [`WheelerData::execute`] instead, so that the edit can be undone.
+impl WheelerData {
+    pub(super) fn insert_item(
+        &mut self,
+        wheel: usize,
+        entry: usize,
+        index: usize,
+        item: WheelItem,
+    ) -> Option<()> {
+        self.wheels
+            .get_mut(wheel)?
+            .entries
+            .get_mut(entry)?
+            .insert_item(index, item)
+    }
+
+    pub(super) fn remove_item(
+        &mut self,
+        wheel: usize,
+        entry: usize,
+        index: usize,
+    ) -> Option<WheelItem> {
+        self.wheels
+            .get_mut(wheel)?
+            .entries
+            .get_mut(entry)?
+            .remove_item(index)
+    }
+
+    pub(super) fn insert_entry(
+        &mut self,
+        wheel: usize,
+        index: usize,
+        entry: WheelEntry,
+    ) -> Option<()> {
+        let wheel = self.wheels.get_mut(wheel)?;
+        if index > wheel.entries.len() {
+            return None;
+        }
+        wheel.entries.insert(index, entry);
+        Some(())
+    }
+
+    pub(super) fn remove_entry(&mut self, wheel: usize, index: usize) -> Option<WheelEntry> {
+        let wheel = self.wheels.get_mut(wheel)?;
+        if index >= wheel.entries.len() {
+            return None;
+        }
+        Some(wheel.entries.remove(index))
+    }
+
+    /// The inserted wheel becomes active.
+    pub(super) fn insert_wheel(&mut self, index: usize, wheel: Wheel) -> Option<()> {
+        if index > self.wheels.len() {
+            return None;
+        }
+        self.wheels.insert(index, wheel);
+        self.active = index;
+        Some(())
+    }
+
+    pub(super) fn remove_wheel(&mut self, index: usize) -> Option<Wheel> {
+        if index >= self.wheels.len() {
+            return None;
+        }
+        let wheel = self.wheels.remove(index);
+        self.active = self.active.min(self.wheels.len().saturating_sub(1));
+        Some(wheel)
+    }
+}
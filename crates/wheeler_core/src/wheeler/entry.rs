@@ -0,0 +1,63 @@
+use super::item::WheelItem;
+
+/// A slot of the wheel.
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct WheelEntry {
+    pub items: Vec<WheelItem>,
+    /// Index of the item activated by this entry. Saved, so the choice survives reloads.
+    selected: usize,
+}
+
+impl WheelEntry {
+    /// The item activated by this entry.
+    #[inline]
+    pub fn current_item(&self) -> Option<&WheelItem> {
+        self.items.get(self.selected_index())
+    }
+
+    /// Index of [`Self::current_item`]. Always in range if `items` is not empty.
+    #[inline]
+    pub fn selected_index(&self) -> usize {
+        self.selected.min(self.items.len().saturating_sub(1))
+    }
+
+    /// Select the next (`delta > 0`) or previous (`delta < 0`) item. Wraps around.
+    pub fn cycle(&mut self, delta: isize) {
+        let len = self.items.len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.selected_index() as isize + delta;
+        self.selected = selected.rem_euclid(len as isize) as usize;
+    }
+
+    /// Insert `item` at `index` and select it.
+    pub(crate) fn insert_item(&mut self, index: usize, item: WheelItem) -> Option<()> {
+        if index > self.items.len() {
+            return None;
+        }
+        self.items.insert(index, item);
+        self.selected = index;
+        Some(())
+    }
+
+    /// Remove the item at `index`. The selection stays on the same item if it remains.
+    pub(crate) fn remove_item(&mut self, index: usize) -> Option<WheelItem> {
+        if index >= self.items.len() {
+            return None;
+        }
+        if index < self.selected_index() {
+            self.selected -= 1;
+        }
+        Some(self.items.remove(index))
+    }
+
+    /// `"2/4"` if this entry has multiple items.
+    pub fn position_label(&self) -> Option<String> {
+        match self.items.len() {
+            0 | 1 => None,
+            len => Some(format!("{}/{len}", self.selected_index() + 1)),
+        }
+    }
+}
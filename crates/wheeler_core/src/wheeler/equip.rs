@@ -0,0 +1,291 @@
+//! Equip items of the wheel to the player.
+use core::ptr::{self, NonNull};
+
+use commonlibsse_ng::re::ActorEquipManager::ActorEquipManager;
+use commonlibsse_ng::re::BGSEquipSlot::BGSEquipSlot;
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::re::ExtraDataList::ExtraDataList;
+use commonlibsse_ng::re::FormTypes::FormType;
+use commonlibsse_ng::re::PlayerCharacter::PlayerCharacter;
+use commonlibsse_ng::re::SpellItem::SpellItem;
+use commonlibsse_ng::re::TESBoundObject::TESBoundObject;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng::re::TESObjectARMO::TESObjectARMO;
+use commonlibsse_ng::re::TESObjectWEAP::TESObjectWEAP;
+use commonlibsse_ng::re::TESShout::TESShout;
+use commonlibsse_ng_re_ext::re_ext::equip::{
+    ActorEquipManagerExt as _, get_left_hand_slot, get_right_hand_slot, get_voice_slot,
+};
+use commonlibsse_ng_re_ext::re_ext::inventory::{
+    find_extra_data_list_by_unique_id, find_player_inventory_entry, get_player_item_count, is_worn,
+    player_inventory_entries,
+};
+use snafu::OptionExt as _;
+
+use super::item::form_name;
+
+/// Hand to equip weapons, spells and scrolls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipHand {
+    Right,
+    Left,
+}
+
+impl EquipHand {
+    fn slot(self) -> *mut BGSEquipSlot {
+        match self {
+            Self::Right => get_right_hand_slot(),
+            Self::Left => get_left_hand_slot(),
+        }
+    }
+}
+
+/// Equip (or use, for potions) `form_id` from the player's inventory or spells.
+///
+/// - `unique_id`: equip this instance. If it is gone, any instance.
+/// - `hand`: ignored for items without hands. (e.g. armor, ammo, shout)
+pub(crate) fn equip_form(
+    form_id: FormID,
+    unique_id: Option<u16>,
+    hand: EquipHand,
+) -> Result<(), EquipError> {
+    let form = TESForm::lookup_by_id(form_id).context(NotFoundFormSnafu { form_id })?;
+    let form = unsafe { form.as_ref() };
+    let (pc, manager) = player_and_manager().context(NotReadySnafu)?;
+
+    match form.get_form_type() {
+        FormType::Weapon
+        | FormType::Armor
+        | FormType::Ammo
+        | FormType::Light
+        | FormType::AlchemyItem
+        | FormType::Ingredient
+        | FormType::Scroll => {
+            let entry =
+                find_player_inventory_entry(form_id).with_context(|| NotInInventorySnafu {
+                    name: form_name(form),
+                })?;
+            let extra_list = unique_id
+                .and_then(|unique_id| find_extra_data_list_by_unique_id(entry, unique_id))
+                .map_or(ptr::null_mut(), |extra_list| {
+                    ptr::from_ref::<ExtraDataList>(extra_list).cast_mut()
+                });
+            let slot = match form.get_form_type() {
+                FormType::Weapon | FormType::Scroll => hand.slot(),
+                FormType::Light => get_left_hand_slot(),
+                _ => ptr::null_mut(),
+            };
+            let object = NonNull::from(form).cast::<TESBoundObject>(); // unsafe downcast
+
+            manager.equip_object(
+                &mut pc.__base.__base,
+                object.as_ptr(),
+                extra_list,
+                1,
+                slot,
+                false,
+                false,
+                true,
+                false,
+            );
+        }
+        FormType::Spell => {
+            let spell = NonNull::from(form).cast::<SpellItem>(); // unsafe downcast
+            if !pc.__base.__base.has_spell(spell.as_ptr()) {
+                return NotKnownSnafu {
+                    name: form_name(form),
+                }
+                .fail();
+            }
+            let slot = match is_power(unsafe { spell.as_ref() }) {
+                true => get_voice_slot(),
+                false => hand.slot(),
+            };
+            manager.equip_spell(&mut pc.__base.__base, spell.as_ptr(), slot);
+        }
+        FormType::Shout => {
+            let shout = NonNull::from(form).cast::<TESShout>(); // unsafe downcast
+            manager.equip_shout(&mut pc.__base.__base, shout.as_ptr());
+        }
+        _ => {
+            return UnsupportedSnafu {
+                name: form_name(form),
+            }
+            .fail();
+        }
+    }
+
+    Ok(())
+}
+
+/// Empty the slot of `hand`.
+pub(crate) fn unequip_hand(hand: EquipHand) -> Result<(), EquipError> {
+    let (pc, manager) = player_and_manager().context(NotReadySnafu)?;
+    unsafe { manager.clean_slot(ptr::from_mut(pc), hand.slot()) };
+    Ok(())
+}
+
+/// Unequip worn armor and clothing that has no slot in `keep_slots`. (biped slot mask)
+pub(crate) fn unequip_armor_outside(keep_slots: u32) -> Result<(), EquipError> {
+    let (pc, manager) = player_and_manager().context(NotReadySnafu)?;
+
+    // Unequipping changes the inventory. Collect first.
+    let mut worn = Vec::new();
+    for entry in player_inventory_entries() {
+        let Some(object) = entry.get_object() else {
+            continue;
+        };
+        let slots = armor_slot_mask(object.__base.__base.get_form());
+        if slots == 0 || slots & keep_slots != 0 {
+            continue;
+        }
+        let Some(extra_lists) = entry.extraLists.as_ref() else {
+            continue;
+        };
+        for extra_list in extra_lists.iter().filter(|extra_list| is_worn(extra_list)) {
+            worn.push((
+                ptr::from_ref(object).cast_mut(),
+                ptr::from_ref::<ExtraDataList>(extra_list).cast_mut(),
+            ));
+        }
+    }
+
+    for (object, extra_list) in worn {
+        manager.unequip_object(
+            &mut pc.__base.__base,
+            object,
+            extra_list,
+            1,
+            ptr::null_mut(),
+            false,
+            false,
+            true,
+            false,
+            ptr::null(),
+        );
+    }
+    Ok(())
+}
+
+/// Is `form` equipped by the player? Spells are checked in both hands, powers and shouts in the
+/// voice slot.
+///
+/// - `unique_id`: check this instance. If it is gone, any instance.
+pub(crate) fn is_equipped(form: &TESForm, unique_id: Option<u16>) -> bool {
+    match form.get_form_type() {
+        FormType::Spell | FormType::Shout => {
+            let Some(pc) = PlayerCharacter::get_singleton() else {
+                return false;
+            };
+            let actor = &pc.__base.__base;
+            let is_form = |equipped: &TESForm| equipped.formID == form.formID;
+
+            [false, true].into_iter().any(|left| {
+                actor
+                    .get_equipped_object(left)
+                    .is_some_and(|equipped| is_form(unsafe { equipped.as_ref() }))
+            }) || unsafe { actor.selectedPower.as_ref() }.is_some_and(is_form)
+        }
+        _ => {
+            let Some(entry) = find_player_inventory_entry(form.formID) else {
+                return false;
+            };
+            let instance =
+                unique_id.and_then(|unique_id| find_extra_data_list_by_unique_id(entry, unique_id));
+            match instance {
+                Some(extra_list) => is_worn(extra_list),
+                None => entry
+                    .extraLists
+                    .as_ref()
+                    .is_some_and(|extra_lists| extra_lists.iter().any(is_worn)),
+            }
+        }
+    }
+}
+
+/// Can the player equip `form` now? `false` if the item is not in the inventory, or the spell or
+/// shout is not learned yet.
+pub(crate) fn is_available(form: &TESForm) -> bool {
+    let Some(pc) = PlayerCharacter::get_singleton() else {
+        return false;
+    };
+
+    match form.get_form_type() {
+        FormType::Spell => {
+            let spell = NonNull::from(form).cast::<SpellItem>(); // unsafe downcast
+            pc.__base.__base.has_spell(spell.as_ptr())
+        }
+        FormType::Shout => {
+            let shout = NonNull::from(form).cast::<TESShout>(); // unsafe downcast
+            pc.__base.__base.has_shout(shout.as_ptr())
+        }
+        _ => get_player_item_count(form.formID) > 0,
+    }
+}
+
+/// Biped slots of armor or clothing. `0` for other forms.
+pub(crate) fn armor_slot_mask(form: &TESForm) -> u32 {
+    if form.get_form_type() != FormType::Armor {
+        return 0;
+    }
+    let armor = unsafe { NonNull::from(form).cast::<TESObjectARMO>().as_ref() }; // unsafe downcast
+    armor.get_slot_mask().bits()
+}
+
+/// Is the right hand holding a two-handed weapon? (including bows and crossbows)
+pub(crate) fn is_right_hand_two_handed() -> bool {
+    let Some(pc) = PlayerCharacter::get_singleton() else {
+        return false;
+    };
+    pc.__base
+        .__base
+        .get_equipped_object(false)
+        .map(|form| unsafe { form.as_ref() })
+        .is_some_and(is_two_handed_weapon)
+}
+
+pub(crate) fn is_two_handed_weapon(form: &TESForm) -> bool {
+    if form.get_form_type() != FormType::Weapon {
+        return false;
+    }
+    let weapon = unsafe { NonNull::from(form).cast::<TESObjectWEAP>().as_ref() }; // unsafe downcast
+    weapon.is_two_handed_sword()
+        || weapon.is_two_handed_axe()
+        || weapon.is_bow()
+        || weapon.is_crossbow()
+}
+
+/// Powers and lesser powers go to the voice slot.
+fn is_power(spell: &SpellItem) -> bool {
+    use commonlibsse_ng::re::MagicSystem::SpellType;
+
+    matches!(
+        spell.get_spell_type(),
+        SpellType::Power | SpellType::LesserPower
+    )
+}
+
+fn player_and_manager() -> Option<(&'static mut PlayerCharacter, &'static mut ActorEquipManager)> {
+    let pc = unsafe { PlayerCharacter::get_singleton_mut()? };
+    let manager = ActorEquipManager::get_singleton_mut()?;
+    Some((pc, manager))
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub enum EquipError {
+    /// Not found form. (The plugin may be removed) form id: {form_id:?}
+    NotFoundForm { form_id: FormID },
+
+    /// Not in the inventory: {name}
+    NotInInventory { name: String },
+
+    /// Not learned yet: {name}
+    NotKnown { name: String },
+
+    /// Cannot equip this kind of item: {name}
+    Unsupported { name: String },
+
+    /// Player or ActorEquipManager is not ready yet.
+    NotReady,
+}
@@ -0,0 +1,107 @@
+//! Fuzzy name matching of the search.
+//!
+//! The characters of the query must appear in the name in order, ignoring case. (e.g. `ebsw` matches
+//! `Ebony Sword`) Names are the localized names from the game, so any script works.
+
+/// A matched character scores this,
+const CHAR_SCORE: u32 = 1;
+/// plus this if it follows the previous matched character,
+const CONSECUTIVE_BONUS: u32 = 5;
+/// plus this if it starts a word.
+const WORD_START_BONUS: u32 = 3;
+
+/// Score of `name` for `query`. Higher is better. `None` if it doesn't match.
+///
+/// An empty query matches nothing.
+pub(crate) fn score(query: &str, name: &str) -> Option<u32> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return None;
+    }
+    let name: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut start = 0;
+    for query_char in query {
+        let found = start + name[start..].iter().position(|c| *c == query_char)?;
+        score += CHAR_SCORE;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if found == 0 || !name[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        previous = Some(found);
+        start = found + 1;
+    }
+    Some(score)
+}
+
+/// Candidates matching `query`, best first. Ties go to the shorter name, then to the earlier one.
+pub(crate) fn rank<T>(query: &str, candidates: impl IntoIterator<Item = (T, String)>) -> Vec<T> {
+    let mut matches: Vec<_> = candidates
+        .into_iter()
+        .filter_map(|(candidate, name)| {
+            let score = score(query, &name)?;
+            Some((candidate, score, name.chars().count()))
+        })
+        .collect();
+    matches.sort_by(|(_, a_score, a_len), (_, b_score, b_len)| {
+        b_score.cmp(a_score).then(a_len.cmp(b_len))
+    });
+    matches
+        .into_iter()
+        .map(|(candidate, _, _)| candidate)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn subsequence() {
+        assert!(score("ebsw", "Ebony Sword").is_some());
+        assert!(score("sword", "Ebony Sword").is_some());
+        assert_eq!(score("swe", "Ebony Sword"), None);
+        assert_eq!(score("", "Ebony Sword"), None);
+        assert_eq!(score("a", ""), None);
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert_eq!(score("EBONY", "ebony sword"), score("ebony", "Ebony Sword"));
+        // Not only ASCII
+        assert!(score("щит", "Железный Щит").is_some());
+        assert!(score("ÄXT", "Stahläxte").is_some());
+    }
+
+    #[test]
+    fn localized() {
+        assert!(score("鋼鉄", "鋼鉄の剣").is_some());
+        assert!(score("剣", "鋼鉄の剣").is_some());
+        assert_eq!(score("盾", "鋼鉄の剣"), None);
+    }
+
+    #[test]
+    fn consecutive_and_word_starts_score_higher() {
+        assert!(score("bo", "Glass Bow") > score("bw", "Glass Bow"));
+        // `s` of `Steel` starts a word, `s` of `Glass` doesn't.
+        assert!(score("sw", "Steel Sword") > score("sw", "Glass Bow"));
+    }
+
+    #[test]
+    fn ranking() {
+        let candidates = [
+            (0, "Glass Bow".to_string()),
+            (1, "Steel Sword".to_string()),
+            (2, "Iron Dagger".to_string()),
+            (3, "Sword".to_string()),
+        ];
+        assert_eq!(rank("sword", candidates.clone()), [3, 1]);
+        assert_eq!(rank("sw", candidates.clone()), [3, 1, 0]);
+        assert_eq!(rank("xyz", candidates), Vec::<i32>::new());
+    }
+}
@@ -0,0 +1,315 @@
+//! Undo/redo of the edit mode.
+//!
+//! Every mutation of the wheels is an [`Edit`]. Applying an edit returns its inverse, which is
+//! what the undo stack keeps.
+use std::collections::VecDeque;
+
+use super::{Wheel, WheelEntry, WheelItem, WheelerData};
+
+/// Older edits are dropped beyond this.
+const MAX_HISTORY: usize = 100;
+
+/// A reversible mutation of the wheels. Indices are of the state before applying.
+#[derive(Debug, Clone)]
+pub(crate) enum Edit {
+    InsertItem {
+        wheel: usize,
+        entry: usize,
+        index: usize,
+        item: WheelItem,
+    },
+    RemoveItem {
+        wheel: usize,
+        entry: usize,
+        index: usize,
+    },
+    InsertEntry {
+        wheel: usize,
+        index: usize,
+        entry: WheelEntry,
+    },
+    RemoveEntry {
+        wheel: usize,
+        index: usize,
+    },
+    InsertWheel {
+        index: usize,
+        wheel: Wheel,
+    },
+    RemoveWheel {
+        index: usize,
+    },
+    /// Applied in order, undone in reverse order, as a single step.
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    /// Returns the inverse, or `None` if nothing was changed. (e.g. out of range)
+    fn apply(self, data: &mut WheelerData) -> Option<Self> {
+        Some(match self {
+            Self::InsertItem {
+                wheel,
+                entry,
+                index,
+                item,
+            } => {
+                data.insert_item(wheel, entry, index, item)?;
+                Self::RemoveItem {
+                    wheel,
+                    entry,
+                    index,
+                }
+            }
+            Self::RemoveItem {
+                wheel,
+                entry,
+                index,
+            } => Self::InsertItem {
+                item: data.remove_item(wheel, entry, index)?,
+                wheel,
+                entry,
+                index,
+            },
+            Self::InsertEntry {
+                wheel,
+                index,
+                entry,
+            } => {
+                data.insert_entry(wheel, index, entry)?;
+                Self::RemoveEntry { wheel, index }
+            }
+            Self::RemoveEntry { wheel, index } => Self::InsertEntry {
+                entry: data.remove_entry(wheel, index)?,
+                wheel,
+                index,
+            },
+            Self::InsertWheel { index, wheel } => {
+                data.insert_wheel(index, wheel)?;
+                Self::RemoveWheel { index }
+            }
+            Self::RemoveWheel { index } => Self::InsertWheel {
+                wheel: data.remove_wheel(index)?,
+                index,
+            },
+            Self::Batch(edits) => {
+                let mut inverses = Vec::with_capacity(edits.len());
+                for edit in edits {
+                    match edit.apply(data) {
+                        Some(inverse) => inverses.push(inverse),
+                        None => {
+                            // Roll back, so that a batch is all or nothing.
+                            for inverse in inverses.into_iter().rev() {
+                                inverse.apply(data);
+                            }
+                            return None;
+                        }
+                    }
+                }
+                inverses.reverse();
+                Self::Batch(inverses)
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    /// Inverses of the applied edits. The last is the newest.
+    undo: VecDeque<Edit>,
+    /// Inverses of the undone edits.
+    redo: Vec<Edit>,
+}
+
+impl History {
+    pub(crate) const fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, inverse: Edit) {
+        if self.undo.len() >= MAX_HISTORY {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(inverse);
+    }
+}
+
+impl WheelerData {
+    /// Apply `edit` and record it for undo. A new edit drops the redo history.
+    pub(super) fn execute(&mut self, edit: Edit) -> Option<()> {
+        let inverse = edit.apply(self)?;
+        self.history.push_undo(inverse);
+        self.history.redo.clear();
+        Some(())
+    }
+
+    pub(super) fn undo(&mut self) {
+        let Some(inverse) = self.history.undo.pop_back() else {
+            return;
+        };
+        if let Some(redo) = inverse.apply(self) {
+            self.history.redo.push(redo);
+        }
+    }
+
+    pub(super) fn redo(&mut self) {
+        let Some(edit) = self.history.redo.pop() else {
+            return;
+        };
+        if let Some(inverse) = edit.apply(self) {
+            self.history.push_undo(inverse);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use commonlibsse_ng::re::BSCoreTypes::FormID;
+    use pretty_assertions::assert_eq;
+
+    fn item(id: u32) -> WheelItem {
+        WheelItem::Form {
+            form_id: FormID::new(id),
+            unique_id: None,
+        }
+    }
+
+    /// A wheel with an entry holding `ids`.
+    fn data_with_items(ids: &[u32]) -> WheelerData {
+        let mut data = WheelerData::new();
+        let mut entry = WheelEntry::default();
+        entry.items = ids.iter().copied().map(item).collect();
+        let mut wheel = Wheel::new();
+        wheel.entries.push(entry);
+        data.wheels.push(wheel);
+        data
+    }
+
+    fn items(data: &WheelerData) -> Vec<WheelItem> {
+        data.wheels[0].entries[0].items.clone()
+    }
+
+    fn insert_item(index: usize, id: u32) -> Edit {
+        Edit::InsertItem {
+            wheel: 0,
+            entry: 0,
+            index,
+            item: item(id),
+        }
+    }
+
+    #[test]
+    fn undo_redo_round_trip() {
+        let mut data = data_with_items(&[1]);
+        data.execute(insert_item(1, 2)).unwrap();
+        data.execute(Edit::InsertEntry {
+            wheel: 0,
+            index: 1,
+            entry: WheelEntry::default(),
+        })
+        .unwrap();
+        data.execute(Edit::RemoveItem {
+            wheel: 0,
+            entry: 0,
+            index: 0,
+        })
+        .unwrap();
+        assert_eq!(items(&data), [item(2)]);
+        assert_eq!(data.wheels[0].entries.len(), 2);
+
+        data.undo();
+        assert_eq!(items(&data), [item(1), item(2)]);
+        data.undo();
+        assert_eq!(data.wheels[0].entries.len(), 1);
+        data.undo();
+        assert_eq!(items(&data), [item(1)]);
+        // Nothing left to undo.
+        data.undo();
+        assert_eq!(items(&data), [item(1)]);
+
+        data.redo();
+        assert_eq!(items(&data), [item(1), item(2)]);
+        data.redo();
+        assert_eq!(data.wheels[0].entries.len(), 2);
+        data.redo();
+        assert_eq!(items(&data), [item(2)]);
+        // Nothing left to redo.
+        data.redo();
+        assert_eq!(items(&data), [item(2)]);
+        assert_eq!(data.history.undo.len(), 3);
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut data = data_with_items(&[]);
+        data.execute(insert_item(0, 1)).unwrap();
+        data.undo();
+        assert_eq!(data.history.redo.len(), 1);
+
+        // A failed edit changes nothing, and keeps the redo.
+        assert_eq!(data.execute(insert_item(5, 2)), None);
+        assert_eq!(data.history.redo.len(), 1);
+
+        data.execute(insert_item(0, 2)).unwrap();
+        assert!(data.history.redo.is_empty());
+        data.redo();
+        assert_eq!(items(&data), [item(2)]);
+    }
+
+    #[test]
+    fn batch_is_one_step() {
+        let mut data = data_with_items(&[1]);
+        data.execute(Edit::Batch(vec![insert_item(1, 2), insert_item(2, 3)]))
+            .unwrap();
+        assert_eq!(items(&data), [item(1), item(2), item(3)]);
+
+        data.undo();
+        assert_eq!(items(&data), [item(1)]);
+        data.redo();
+        assert_eq!(items(&data), [item(1), item(2), item(3)]);
+    }
+
+    #[test]
+    fn failed_batch_rolls_back() {
+        let mut data = data_with_items(&[1]);
+        let batch = Edit::Batch(vec![
+            insert_item(1, 2),
+            Edit::RemoveItem {
+                wheel: 0,
+                entry: 0,
+                index: 0,
+            },
+            // Out of range
+            Edit::RemoveEntry { wheel: 0, index: 5 },
+        ]);
+        assert_eq!(data.execute(batch), None);
+        assert_eq!(items(&data), [item(1)]);
+        assert!(data.history.undo.is_empty());
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut data = data_with_items(&[]);
+        let count = MAX_HISTORY + 50;
+        for id in 0..count {
+            data.execute(insert_item(0, id as u32)).unwrap();
+        }
+        assert_eq!(data.history.undo.len(), MAX_HISTORY);
+
+        for _ in 0..count {
+            data.undo();
+        }
+        // The oldest edits can't be undone.
+        assert_eq!(items(&data).len(), count - MAX_HISTORY);
+        assert_eq!(items(&data)[0], item(49));
+        assert_eq!(data.history.redo.len(), MAX_HISTORY);
+    }
+}
@@ -0,0 +1,320 @@
+use core::ptr::NonNull;
+
+use commonlibsse_ng::re::ActorValues::ActorValue;
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::re::FormTypes::FormType;
+use commonlibsse_ng::re::MagicItem::MagicItem;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng::re::TESObjectWEAP::TESObjectWEAP;
+use commonlibsse_ng_re_ext::re_ext::magic::{
+    EffectInfo, get_magic_item_description, get_magic_item_effects,
+};
+use snafu::{OptionExt as _, ResultExt as _};
+
+use super::armor_set::{ArmorSet, ArmorSetError};
+use super::equip::{EquipError, EquipHand, equip_form, is_available, is_equipped};
+use super::loadout::{Loadout, LoadoutError};
+use super::smart;
+use super::stats::item_stats;
+use super::tooltip::{StatLine, TooltipContent};
+use crate::config::ConsumableRule;
+use crate::rendering::rich_text::RichText;
+use crate::rendering::texture_manager::{Element, IconImageType, IconState};
+
+/// What a slot of an entry holds.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WheelItem {
+    /// Inventory item or magic.
+    Form {
+        #[serde(with = "serde_form_id")]
+        form_id: FormID,
+        /// Points a specific instance. (e.g. a tempered sword)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unique_id: Option<u16>,
+    },
+    /// Several items equipped at once.
+    Loadout(Loadout),
+    /// Armor and clothing swapped at once.
+    ArmorSet(ArmorSet),
+    /// The best matching consumable of the inventory at activation.
+    Smart(ConsumableRule),
+}
+
+impl WheelItem {
+    #[inline]
+    pub fn form(&self) -> Option<NonNull<TESForm>> {
+        match self {
+            Self::Form { form_id, .. } => TESForm::lookup_by_id(*form_id),
+            Self::Loadout(_) | Self::ArmorSet(_) | Self::Smart(_) => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::Form { .. } => self
+                .form()
+                .map(|form| form_name(unsafe { form.as_ref() }))
+                .unwrap_or_default(),
+            Self::Loadout(loadout) => loadout.display_name(),
+            Self::ArmorSet(armor_set) => armor_set.display_name(),
+            Self::Smart(rule) => rule.name.clone(),
+        }
+    }
+
+    /// Default icon when there is no custom icon for the form.
+    ///
+    /// A loadout shows the icon of its first part.
+    pub fn icon_type(&self) -> IconImageType {
+        let form = match self {
+            Self::Form { .. } => self.form(),
+            Self::Loadout(loadout) => loadout
+                .parts
+                .iter()
+                .find_map(|part| TESForm::lookup_by_id(part.form_id)),
+            Self::ArmorSet(_) => return IconImageType::ArmorDefault,
+            Self::Smart(rule) => return smart::icon_type(rule),
+        };
+        let Some(form) = form else {
+            return IconImageType::IconDefault;
+        };
+
+        let form = unsafe { form.as_ref() };
+        match form.get_form_type() {
+            FormType::Weapon => weapon_icon_type(form),
+            FormType::Armor => IconImageType::ArmorDefault,
+            FormType::Ammo => IconImageType::Arrow,
+            FormType::Light => IconImageType::Torch,
+            FormType::AlchemyItem => IconImageType::PotionDefault,
+            FormType::Ingredient => IconImageType::Food,
+            FormType::Scroll => IconImageType::Scroll,
+            FormType::Spell => IconImageType::SpellDefault,
+            FormType::Shout => IconImageType::Shout,
+            _ => IconImageType::IconDefault,
+        }
+    }
+
+    /// Tint state of the icon. Unavailable wins over hovered, and hovered over equipped.
+    ///
+    /// Only single forms can be unavailable or equipped.
+    pub fn icon_state(&self, is_hovered: bool) -> IconState {
+        let hovered_or = |state| match is_hovered {
+            true => IconState::Hovered,
+            false => state,
+        };
+        let Self::Form { unique_id, .. } = self else {
+            return hovered_or(IconState::Normal);
+        };
+        let Some(form) = self.form() else {
+            return IconState::Unavailable;
+        };
+        let form = unsafe { form.as_ref() };
+
+        if !is_available(form) {
+            return IconState::Unavailable;
+        }
+        hovered_or(match is_equipped(form, *unique_id) {
+            true => IconState::Equipped,
+            false => IconState::Normal,
+        })
+    }
+
+    /// Accent of spell and scroll icons, from their first effect.
+    ///
+    /// `None` means the icon type decides it.
+    pub fn element(&self) -> Option<Element> {
+        let form = unsafe { self.form()?.as_ref() };
+        if !matches!(form.get_form_type(), FormType::Spell | FormType::Scroll) {
+            return None;
+        }
+        let magic_item = magic_item_of(form)?;
+        let effects = get_magic_item_effects(unsafe { magic_item.as_ref() });
+        effect_element(effects.first()?)
+    }
+
+    /// Contents of the description panel shown while hovered.
+    pub fn tooltip(&self) -> Option<TooltipContent> {
+        let unique_id = match self {
+            Self::Form { unique_id, .. } => unique_id,
+            Self::Loadout(loadout) => return Some(loadout_tooltip(loadout)),
+            Self::ArmorSet(armor_set) => return Some(armor_set_tooltip(armor_set)),
+            Self::Smart(rule) => return Some(smart_tooltip(rule)),
+        };
+        let form = unsafe { self.form()?.as_ref() };
+
+        let markup = magic_item_of(form)
+            .map(|magic_item| get_magic_item_description(magic_item.as_ptr()))
+            .filter(|markup| !markup.trim().is_empty());
+
+        // The plain text comes from the parsed one too, so that entities (e.g. `&lt;`) are decoded.
+        let rich_description = markup
+            .as_deref()
+            .map(RichText::parse)
+            .filter(|rich_description| !rich_description.is_blank());
+        let description = rich_description.as_ref().map(RichText::to_plain_string);
+
+        Some(TooltipContent {
+            title: form_name(form),
+            description,
+            rich_description,
+            stats: item_stats(form, *unique_id),
+        })
+    }
+
+    /// Equip (or use) this item.
+    ///
+    /// - `hand`: for weapons, spells and scrolls. Ignored by loadouts and armor sets.
+    ///   Smart entries give it to the resolved item. (e.g. poison)
+    ///
+    /// # Errors
+    /// If this item (or a part of the loadout) could not be equipped.
+    pub(crate) fn activate(&self, hand: EquipHand) -> Result<(), ActivateError> {
+        match self {
+            Self::Form { form_id, unique_id } => {
+                equip_form(*form_id, *unique_id, hand).context(EquipSnafu)
+            }
+            Self::Loadout(loadout) => loadout.equip().context(LoadoutSnafu),
+            Self::ArmorSet(armor_set) => armor_set.equip().context(ArmorSetSnafu),
+            Self::Smart(rule) => {
+                let form_id = smart::resolve(rule).context(NoMatchSnafu {
+                    name: rule.name.as_str(),
+                })?;
+                equip_form(form_id, None, hand).context(EquipSnafu)
+            }
+        }
+    }
+}
+
+/// Parts are listed as stats.
+fn loadout_tooltip(loadout: &Loadout) -> TooltipContent {
+    TooltipContent {
+        title: loadout.display_name(),
+        description: None,
+        rich_description: None,
+        stats: loadout
+            .parts
+            .iter()
+            .map(|part| StatLine::new(format!("{}: {}", part.slot, part.name())))
+            .collect(),
+    }
+}
+
+/// Pieces are listed as stats.
+fn armor_set_tooltip(armor_set: &ArmorSet) -> TooltipContent {
+    TooltipContent {
+        title: armor_set.display_name(),
+        description: armor_set
+            .unequip_uncovered
+            .then(|| "Unequips armor in the other slots.".to_string()),
+        rich_description: None,
+        stats: armor_set
+            .pieces
+            .iter()
+            .map(|piece| StatLine::with_icon(IconImageType::ArmorDefault, piece.name()))
+            .collect(),
+    }
+}
+
+/// Shows what would be used now.
+fn smart_tooltip(rule: &ConsumableRule) -> TooltipContent {
+    let resolved = smart::resolve(rule)
+        .and_then(TESForm::lookup_by_id)
+        .map_or_else(
+            || "None in the inventory".to_string(),
+            |form| form_name(unsafe { form.as_ref() }),
+        );
+    TooltipContent {
+        title: rule.name.clone(),
+        description: None,
+        rich_description: None,
+        stats: vec![StatLine::with_icon(smart::icon_type(rule), resolved)],
+    }
+}
+
+#[inline]
+pub(crate) fn form_name(form: &TESForm) -> String {
+    form.get_name().to_string_lossy().into_owned()
+}
+
+/// Elemental damage first, so that e.g. a fire spell is `Fire` rather than `Destruction`.
+const fn effect_element(effect: &EffectInfo) -> Option<Element> {
+    Some(match (effect.resist, effect.skill) {
+        (ActorValue::ResistFire, _) => Element::Fire,
+        (ActorValue::ResistFrost, _) => Element::Frost,
+        (ActorValue::ResistShock, _) => Element::Shock,
+        (_, ActorValue::Alteration) => Element::Alteration,
+        (_, ActorValue::Conjuration) => Element::Conjuration,
+        (_, ActorValue::Destruction) => Element::Destruction,
+        (_, ActorValue::Illusion) => Element::Illusion,
+        (_, ActorValue::Restoration) => Element::Restoration,
+        _ => return None,
+    })
+}
+
+/// Icon of the weapon kind. Warhammers have the two-handed axe type, so they share its icon.
+fn weapon_icon_type(form: &TESForm) -> IconImageType {
+    let weapon = unsafe { NonNull::from(form).cast::<TESObjectWEAP>().as_ref() }; // unsafe downcast
+
+    if weapon.is_one_handed_sword() {
+        IconImageType::SwordOneHanded
+    } else if weapon.is_one_handed_dagger() {
+        IconImageType::Dagger
+    } else if weapon.is_one_handed_axe() {
+        IconImageType::AxeOneHanded
+    } else if weapon.is_one_handed_mace() {
+        IconImageType::Mace
+    } else if weapon.is_two_handed_sword() {
+        IconImageType::SwordTwoHanded
+    } else if weapon.is_two_handed_axe() {
+        IconImageType::AxeTwoHanded
+    } else if weapon.is_bow() {
+        IconImageType::Bow
+    } else if weapon.is_crossbow() {
+        IconImageType::Crossbow
+    } else if weapon.is_staff() {
+        IconImageType::Staff
+    } else if weapon.is_hand_to_hand_melee() {
+        IconImageType::HandToHand
+    } else {
+        IconImageType::IconDefault
+    }
+}
+
+/// `MagicItem` is the base of spell, scroll, potion and ingredient.
+pub(crate) fn magic_item_of(form: &TESForm) -> Option<NonNull<MagicItem>> {
+    match form.get_form_type() {
+        FormType::Spell | FormType::Scroll | FormType::AlchemyItem | FormType::Ingredient => {
+            Some(NonNull::from(form).cast::<MagicItem>()) // unsafe downcast
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub enum ActivateError {
+    /// {source}
+    Equip { source: EquipError },
+
+    /// {source}
+    Loadout { source: LoadoutError },
+
+    /// {source}
+    ArmorSet { source: ArmorSetError },
+
+    /// No item in the inventory matches `{name}`.
+    NoMatch { name: String },
+}
+
+/// `FormID` as `u32`.
+pub(crate) mod serde_form_id {
+    use commonlibsse_ng::re::BSCoreTypes::FormID;
+    use serde::{Deserialize as _, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(form_id: &FormID, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(form_id.get())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<FormID, D::Error> {
+        u32::deserialize(deserializer).map(FormID::new)
+    }
+}
@@ -0,0 +1,210 @@
+//! Loadout: a set of items equipped by one activation.
+use core::fmt;
+
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::re::PlayerCharacter::PlayerCharacter;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng_re_ext::re_ext::inventory::get_equipped_unique_id;
+
+use super::armor_set::find_worn_piece;
+use super::equip::{
+    EquipError, EquipHand, equip_form, is_right_hand_two_handed, is_two_handed_weapon, unequip_hand,
+};
+use super::item::{form_name, serde_form_id};
+
+/// Biped slots of the helmet captured by [`Loadout::from_equipped`]. (head, hair, circlet)
+const HELMET_SLOTS: u32 = 0x1 | 0x2 | 0x1000;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Loadout {
+    /// Shown as the title. If empty, the names of the parts are joined.
+    pub name: String,
+    pub parts: Vec<LoadoutPart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct LoadoutPart {
+    pub slot: LoadoutSlot,
+    #[serde(with = "serde_form_id")]
+    pub form_id: FormID,
+    /// Points a specific instance. (e.g. a tempered sword)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique_id: Option<u16>,
+}
+
+/// Where a part goes. Parts are equipped in this order.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Deserialize, serde::Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadoutSlot {
+    /// Weapon, spell, scroll
+    RightHand,
+    /// Weapon, spell, scroll, shield, torch
+    LeftHand,
+    Ammo,
+    /// Shout or power
+    Voice,
+    /// Armor or clothing. (e.g. helmet)
+    Armor,
+}
+
+impl LoadoutSlot {
+    #[inline]
+    const fn hand(self) -> EquipHand {
+        match self {
+            Self::LeftHand => EquipHand::Left,
+            _ => EquipHand::Right,
+        }
+    }
+}
+
+impl fmt::Display for LoadoutSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::RightHand => "Right hand",
+            Self::LeftHand => "Left hand",
+            Self::Ammo => "Ammo",
+            Self::Voice => "Voice",
+            Self::Armor => "Armor",
+        })
+    }
+}
+
+impl Loadout {
+    /// Title of the slot and the tooltip.
+    pub fn display_name(&self) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+        self.parts
+            .iter()
+            .map(LoadoutPart::name)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// Equip all parts. A failed part does not stop the others.
+    ///
+    /// # Errors
+    /// If any part failed, with the failed parts.
+    pub(crate) fn equip(&self) -> Result<(), LoadoutError> {
+        let mut parts: Vec<&LoadoutPart> = self.parts.iter().collect();
+        parts.sort_by_key(|part| part.slot);
+
+        let mut failed = Vec::new();
+
+        // Equipping the left hand while a two-handed weapon is held would be rejected, or would
+        // drop the new right-hand weapon. So empty the right hand first.
+        let has_left = parts.iter().any(|part| part.slot == LoadoutSlot::LeftHand);
+        if has_left && is_right_hand_two_handed() {
+            if let Err(err) = unequip_hand(EquipHand::Right) {
+                failed.push((LoadoutSlot::RightHand, err));
+            }
+        }
+
+        for part in parts {
+            if let Err(err) = equip_form(part.form_id, part.unique_id, part.slot.hand()) {
+                failed.push((part.slot, err));
+            }
+        }
+
+        match failed.is_empty() {
+            true => Ok(()),
+            false => LoadoutSnafu {
+                name: self.display_name(),
+                failed,
+            }
+            .fail(),
+        }
+    }
+
+    /// Capture the current hands, ammo, voice and helmet of the player.
+    pub(crate) fn from_equipped() -> Option<Self> {
+        let pc = PlayerCharacter::get_singleton()?;
+        let actor = &pc.__base.__base;
+        let mut parts = Vec::new();
+
+        let right = actor.get_equipped_object(false);
+        for (slot, left) in [
+            (LoadoutSlot::RightHand, false),
+            (LoadoutSlot::LeftHand, true),
+        ] {
+            let Some(form) = actor.get_equipped_object(left) else {
+                continue;
+            };
+            let form = unsafe { form.as_ref() };
+            // A two-handed weapon is reported by both hands.
+            if left
+                && right.is_some_and(|right| unsafe { right.as_ref() }.formID == form.formID)
+                && is_two_handed_weapon(form)
+            {
+                continue;
+            }
+            parts.push(LoadoutPart {
+                slot,
+                form_id: form.formID,
+                unique_id: get_equipped_unique_id(actor, left),
+            });
+        }
+
+        if let Some(ammo) = actor.get_current_ammo() {
+            let ammo = unsafe { ammo.cast::<TESForm>().as_ref() }; // upcast
+            parts.push(LoadoutPart::new(LoadoutSlot::Ammo, ammo));
+        }
+        if let Some(power) = unsafe { actor.selectedPower.as_ref() } {
+            parts.push(LoadoutPart::new(LoadoutSlot::Voice, power));
+        }
+        if let Some(helmet) = find_worn_piece(HELMET_SLOTS) {
+            parts.push(LoadoutPart {
+                slot: LoadoutSlot::Armor,
+                form_id: helmet.form_id,
+                unique_id: helmet.unique_id,
+            });
+        }
+
+        match parts.is_empty() {
+            true => None,
+            false => Some(Self {
+                name: String::new(),
+                parts,
+            }),
+        }
+    }
+}
+
+impl LoadoutPart {
+    #[inline]
+    fn new(slot: LoadoutSlot, form: &TESForm) -> Self {
+        Self {
+            slot,
+            form_id: form.formID,
+            unique_id: None,
+        }
+    }
+
+    pub fn form(&self) -> Option<&'static TESForm> {
+        TESForm::lookup_by_id(self.form_id).map(|form| unsafe { form.as_ref() })
+    }
+
+    pub fn name(&self) -> String {
+        self.form().map(form_name).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, snafu::Snafu)]
+#[snafu(display("Failed to equip `{name}`: {}", describe_failures(failed)))]
+pub struct LoadoutError {
+    name: String,
+    failed: Vec<(LoadoutSlot, EquipError)>,
+}
+
+fn describe_failures(failed: &[(LoadoutSlot, EquipError)]) -> String {
+    failed
+        .iter()
+        .map(|(slot, err)| format!("{slot}: {err}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
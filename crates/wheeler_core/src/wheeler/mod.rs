@@ -1,8 +1,43 @@
+mod armor_set;
+mod badge;
+pub mod edit;
+mod entry;
+mod equip;
+mod fuzzy;
+mod history;
+mod item;
+mod loadout;
+pub mod persistence;
+pub mod search;
+pub mod settings;
+mod smart;
+mod stats;
+mod tooltip;
+mod wheel;
+
 use core::ffi::CStr;
 use core::sync::atomic;
+use std::ffi::CString;
+use std::sync::{Mutex, MutexGuard};
+
+use commonlibsse_ng_re_ext::re_ext::hud::debug_notification;
+
+pub use self::armor_set::{ArmorPiece, ArmorSet, ArmorSetError};
+pub use self::badge::{install_inventory_watcher, invalidate_all as invalidate_badges};
+pub use self::entry::WheelEntry;
+pub use self::equip::{EquipError, EquipHand};
+pub use self::item::{ActivateError, WheelItem};
+pub use self::loadout::{Loadout, LoadoutError, LoadoutPart, LoadoutSlot};
+pub use self::stats::TemperTier;
+pub use self::tooltip::{StatLine, TooltipContent};
+pub use self::wheel::Wheel;
 
 pub const WHEEL_WINDOW_ID: &CStr = c"##Wheeler_rs";
 static STATE: AtomicWheelState = AtomicWheelState::new(WheelState::Closed);
+static WHEELER: Mutex<WheelerData> = Mutex::new(WheelerData::new());
+
+/// Mouse move is in raw counts. Convert to 1080p pixels.
+const MOUSE_SENSITIVITY: f32 = 1.0;
 
 #[atomic_enum::atomic_enum]
 #[derive(PartialEq)]
@@ -13,23 +48,249 @@ enum WheelState {
     Closing,
 }
 
+/// Data shared between the input thread and the render thread.
+struct WheelerData {
+    /// Pages of the wheel.
+    wheels: Vec<Wheel>,
+    /// Index of the shown wheel.
+    active: usize,
+    /// Offset from the wheel center. (1080p)
+    cursor: [f32; 2],
+    /// The description is fetched from the game, so reuse it while the same item is hovered.
+    tooltip_cache: Option<(WheelItem, Option<TooltipContent>)>,
+    history: history::History,
+}
+
+impl WheelerData {
+    const fn new() -> Self {
+        Self {
+            wheels: Vec::new(),
+            active: 0,
+            cursor: [0.0, 0.0],
+            tooltip_cache: None,
+            history: history::History::new(),
+        }
+    }
+
+    #[inline]
+    fn active_wheel(&self) -> Option<&Wheel> {
+        self.wheels.get(self.active)
+    }
+
+    #[inline]
+    fn active_wheel_mut(&mut self) -> Option<&mut Wheel> {
+        self.wheels.get_mut(self.active)
+    }
+
+    /// Show the next (`delta > 0`) or previous (`delta < 0`) wheel. Wraps around.
+    fn cycle_wheel(&mut self, delta: isize) {
+        let len = self.wheels.len();
+        if len == 0 {
+            return;
+        }
+        self.active = (self.active.min(len - 1) as isize + delta).rem_euclid(len as isize) as usize;
+    }
+
+    fn tooltip(&mut self) -> Option<TooltipContent> {
+        let item = self.active_wheel()?.hovered_entry()?.current_item()?;
+        match &self.tooltip_cache {
+            Some((cached, content)) if cached == item => content.clone(),
+            _ => {
+                let content = item.tooltip();
+                self.tooltip_cache = Some((item.clone(), content.clone()));
+                content
+            }
+        }
+    }
+}
+
+fn lock() -> MutexGuard<'static, WheelerData> {
+    WHEELER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Is the wheel shown (including open/close transitions)?
+#[inline]
+pub fn is_wheeler_open() -> bool {
+    STATE.load(atomic::Ordering::Acquire) != WheelState::Closed
+}
+
+/// Failures are shown as a HUD notification.
+fn activate(item: Option<WheelItem>, hand: EquipHand) {
+    let Some(item) = item else {
+        return;
+    };
+
+    if let Err(err) = item.activate(hand) {
+        tracing::error!("{err}");
+        if let Ok(message) = CString::new(err.to_string()) {
+            debug_notification(&message);
+        }
+    }
+}
+
 pub struct Wheeler {}
 
 impl Wheeler {
     pub fn update(delta_time: f32) {
         use imgui::sys::{
-            ImVec2, igBeginPopup, igCloseCurrentPopup, igEndPopup, igIsPopupOpen,
+            ImVec2, igBeginPopup, igCloseCurrentPopup, igEndPopup, igGetIO, igOpenPopup_Str,
             igSetNextWindowPos,
         };
 
-        if STATE.load(atomic::Ordering::Acquire) == WheelState::Closed {}
+        match STATE.load(atomic::Ordering::Acquire) {
+            WheelState::Closed => return,
+            WheelState::Opening => {
+                unsafe { igOpenPopup_Str(WHEEL_WINDOW_ID.as_ptr(), 0) };
+                STATE.store(WheelState::Opened, atomic::Ordering::Release);
+            }
+            WheelState::Closing => {
+                unsafe {
+                    if igBeginPopup(WHEEL_WINDOW_ID.as_ptr(), 0) {
+                        igCloseCurrentPopup();
+                        igEndPopup();
+                    }
+                }
+                STATE.store(WheelState::Closed, atomic::Ordering::Release);
+                return;
+            }
+            WheelState::Opened => {}
+        }
 
         unsafe {
-            igIsPopupOpen(WHEEL_WINDOW_ID.as_ptr(), 0);
+            // The popup only keeps the open state. The wheel itself is drawn on the foreground.
             igSetNextWindowPos(ImVec2::new(-100.0, -100.0), 0, ImVec2::zero());
-            igBeginPopup(WHEEL_WINDOW_ID.as_ptr(), 0);
-            igCloseCurrentPopup();
-            igEndPopup();
+            if !igBeginPopup(WHEEL_WINDOW_ID.as_ptr(), 0) {
+                return;
+            }
+        }
+
+        if let Some(io) = unsafe { igGetIO().as_ref() } {
+            let center = [io.DisplaySize.x * 0.5, io.DisplaySize.y * 0.5];
+            let scale =
+                crate::rendering::render_manager::get_resolution_scale_height().unwrap_or(1.0);
+            Self::draw(center, scale, delta_time);
+            settings::draw(scale);
+        }
+
+        unsafe { igEndPopup() };
+    }
+
+    fn draw(center: [f32; 2], scale: f32, _delta_time: f32) {
+        let draw_list = crate::rendering::draw::foreground_draw_list();
+        let mut data = lock();
+        let config = crate::config::get();
+
+        let cursor = data.cursor;
+        let (active, len) = (data.active, data.wheels.len());
+        let results = search::query().map(|query| {
+            let hits = search::find(&data, &query);
+            (query, hits)
+        });
+        let matched_entries: Vec<_> = results
+            .iter()
+            .flat_map(|(_, hits)| hits)
+            .filter(|hit| hit.wheel == active)
+            .map(|hit| hit.entry)
+            .collect();
+
+        let Some(wheel) = data.active_wheel_mut() else {
+            return;
+        };
+        wheel.update_hovered(cursor);
+        let number_keys = &config.controls.number_keys;
+        let show_numbers = number_keys.enabled && number_keys.show_numbers;
+        wheel.draw(
+            draw_list,
+            center,
+            scale,
+            &config.badge,
+            show_numbers,
+            &matched_entries,
+        );
+        wheel::draw_indicators(draw_list, center, scale, active, len, wheel.name.as_deref());
+        if edit::is_edit_mode() {
+            wheel::draw_edit_mode_label(draw_list, center, scale);
         }
+        if let Some((query, hits)) = &results {
+            search::draw(draw_list, &data, query, hits, center, scale);
+        }
+
+        if let Some(content) = data.tooltip() {
+            tooltip::draw(&content, center, scale, &config.tooltip);
+        }
+    }
+
+    pub fn open() {
+        // Charge is consumed without inventory changes. Refresh once per open.
+        badge::invalidate_all();
+        lock().cursor = [0.0, 0.0];
+        STATE.store(WheelState::Opening, atomic::Ordering::Release);
+    }
+
+    pub fn close() {
+        settings::close();
+        search::cancel();
+        STATE.store(WheelState::Closing, atomic::Ordering::Release);
+    }
+
+    pub fn toggle() {
+        match is_wheeler_open() {
+            true => Self::close(),
+            false => Self::open(),
+        }
+    }
+
+    /// Select the next (`delta > 0`) or previous (`delta < 0`) item of the hovered entry.
+    pub fn cycle_hovered_item(delta: isize) {
+        if let Some(entry) = lock()
+            .active_wheel_mut()
+            .and_then(|wheel| wheel.hovered_entry_mut())
+        {
+            entry.cycle(delta);
+        }
+    }
+
+    /// Equip the shown item of the hovered entry. Failures are shown as a HUD notification.
+    pub fn activate_hovered(hand: EquipHand) {
+        // Equipping fires game events. Do not hold the lock meanwhile.
+        let item = lock()
+            .active_wheel()
+            .and_then(Wheel::hovered_entry)
+            .and_then(WheelEntry::current_item)
+            .cloned();
+        activate(item, hand);
+    }
+
+    /// Equip the shown item of the `index`th entry of the active wheel, hovered or not.
+    pub fn activate_entry(index: usize, hand: EquipHand) {
+        let item = lock()
+            .active_wheel()
+            .and_then(|wheel| wheel.entries.get(index))
+            .and_then(WheelEntry::current_item)
+            .cloned();
+        activate(item, hand);
+    }
+
+    /// Show the next (`delta > 0`) or previous (`delta < 0`) wheel.
+    pub fn cycle_wheel(delta: isize) {
+        lock().cycle_wheel(delta);
+    }
+
+    /// `x`, `y`: mouse move delta
+    pub fn update_cursor_pos_mouse(x: f32, y: f32) {
+        let mut data = lock();
+        let [cursor_x, cursor_y] = data.cursor;
+        data.cursor = wheel::clamp_cursor([
+            cursor_x + x * MOUSE_SENSITIVITY,
+            cursor_y + y * MOUSE_SENSITIVITY,
+        ]);
+    }
+
+    /// `x`, `y`: right thumbstick position. (-1.0..=1.0, y is up)
+    pub fn update_cursor_pos_gamepad(x: f32, y: f32) {
+        lock().cursor =
+            wheel::clamp_cursor([x * wheel::CURSOR_MAX_RADIUS, -y * wheel::CURSOR_MAX_RADIUS]);
     }
 }
@@ -0,0 +1,133 @@
+//! Wheels are kept in the SKSE co-save, so every save has its own wheels.
+//!
+//! Register the callbacks to the SKSE serialization interface with [`SERIALIZATION_ID`].
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::skse::interfaces::serialization::SerializationInterface;
+use snafu::ResultExt as _;
+
+use super::item::WheelItem;
+use super::wheel::Wheel;
+
+/// Unique id of this plugin in the co-save.
+pub const SERIALIZATION_ID: u32 = u32::from_be_bytes(*b"WHLR");
+const RECORD_WHEELS: u32 = u32::from_be_bytes(*b"WHLS");
+const RECORD_VERSION: u32 = 1;
+
+/// Contents of [`RECORD_WHEELS`], as TOML.
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct SaveData {
+    wheels: Vec<Wheel>,
+    /// The last shown wheel.
+    active_wheel: usize,
+}
+
+pub fn on_save(intfc: &SerializationInterface) {
+    let data = {
+        let wheeler = super::lock();
+        SaveData {
+            wheels: wheeler.wheels.clone(),
+            active_wheel: wheeler.active,
+        }
+    };
+
+    let text = match toml::to_string(&data).context(FailedToSerializeSnafu) {
+        Ok(text) => text,
+        Err(err) => {
+            tracing::error!("{err}");
+            return;
+        }
+    };
+    if !intfc.write_record(RECORD_WHEELS, RECORD_VERSION, text.as_bytes()) {
+        tracing::error!("Failed to write wheels to the co-save.");
+    }
+}
+
+pub fn on_load(intfc: &SerializationInterface) {
+    while let Some(record) = intfc.get_next_record_info() {
+        if record.type_ != RECORD_WHEELS {
+            continue;
+        }
+
+        let mut bytes = vec![0; record.length as usize];
+        intfc.read_record_data(&mut bytes);
+
+        match parse(&bytes, record.version) {
+            Ok(mut data) => {
+                data.wheels
+                    .iter_mut()
+                    .for_each(|wheel| resolve_form_ids(wheel, intfc));
+
+                let mut wheeler = super::lock();
+                wheeler.active = data.active_wheel.min(data.wheels.len().saturating_sub(1));
+                wheeler.wheels = data.wheels;
+                wheeler.history.clear();
+            }
+            Err(err) => tracing::error!("{err}"),
+        }
+    }
+}
+
+/// Called before loading another save. Wheels of the previous save must not leak.
+pub fn on_revert(_intfc: &SerializationInterface) {
+    let mut wheeler = super::lock();
+    wheeler.wheels.clear();
+    wheeler.active = 0;
+    wheeler.history.clear();
+}
+
+fn parse(bytes: &[u8], version: u32) -> Result<SaveData, PersistenceError> {
+    if version != RECORD_VERSION {
+        return UnsupportedVersionSnafu { version }.fail();
+    }
+    let text = core::str::from_utf8(bytes).context(InvalidUtf8Snafu)?;
+    toml::from_str(text).context(FailedToParseSnafu)
+}
+
+/// Load order may have changed since the save. Items of removed plugins are dropped.
+fn resolve_form_ids(wheel: &mut Wheel, intfc: &SerializationInterface) {
+    for entry in &mut wheel.entries {
+        entry.items.retain_mut(|item| match item {
+            WheelItem::Form { form_id, .. } => resolve_form_id(form_id, intfc),
+            WheelItem::Loadout(loadout) => {
+                loadout
+                    .parts
+                    .retain_mut(|part| resolve_form_id(&mut part.form_id, intfc));
+                !loadout.parts.is_empty()
+            }
+            WheelItem::ArmorSet(armor_set) => {
+                armor_set
+                    .pieces
+                    .retain_mut(|piece| resolve_form_id(&mut piece.form_id, intfc));
+                !armor_set.pieces.is_empty()
+            }
+            WheelItem::Smart(_) => true,
+        });
+    }
+}
+
+/// Returns `false` if the plugin of `form_id` is gone.
+fn resolve_form_id(form_id: &mut FormID, intfc: &SerializationInterface) -> bool {
+    match intfc.resolve_form_id(*form_id) {
+        Some(resolved) => {
+            *form_id = resolved;
+            true
+        }
+        None => false,
+    }
+}
+
+#[derive(Debug, snafu::Snafu)]
+pub enum PersistenceError {
+    /// Failed to serialize wheels: {source}
+    FailedToSerialize { source: toml::ser::Error },
+
+    /// Unsupported wheels record version: {version}
+    UnsupportedVersion { version: u32 },
+
+    /// Wheels record is not UTF-8: {source}
+    InvalidUtf8 { source: core::str::Utf8Error },
+
+    /// Failed to parse wheels record: {source}
+    FailedToParse { source: toml::de::Error },
+}
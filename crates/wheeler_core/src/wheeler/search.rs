@@ -0,0 +1,146 @@
+//! Type to search the items of all wheels by name.
+//!
+//! While searching, [`crate::user_input::input`] sends the typed characters here and blocks the
+//! keyboard buttons, so that typing doesn't run the bindings or move the player.
+use std::sync::{Mutex, MutexGuard};
+
+use imgui::sys::{ImDrawList, igGetFontSize};
+
+use super::{EquipHand, WheelItem, WheelerData, fuzzy, lock as lock_wheeler};
+use crate::rendering::color::Color;
+use crate::rendering::draw::{calc_text_size, draw_text};
+
+/// Max results listed in the wheel center.
+const MAX_SHOWN_RESULTS: usize = 5;
+/// Color of the query and the results other than the top match.
+const DIM: Color = Color::rgb(0xC0, 0xC0, 0xC0);
+
+/// `Some` while searching.
+static QUERY: Mutex<Option<String>> = Mutex::new(None);
+
+/// Position of an item in the wheels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SearchHit {
+    pub wheel: usize,
+    pub entry: usize,
+    pub item: usize,
+}
+
+/// Start typing a query. Keeps the query if already searching.
+pub fn start() {
+    lock().get_or_insert_with(String::new);
+}
+
+pub fn cancel() {
+    *lock() = None;
+}
+
+#[inline]
+pub fn is_searching() -> bool {
+    lock().is_some()
+}
+
+/// The query while searching.
+pub fn query() -> Option<String> {
+    lock().clone()
+}
+
+/// A typed character. Only letters, digits and the punctuation of names are taken.
+pub(crate) fn push_char(c: char) {
+    if !(c.is_alphanumeric() || matches!(c, ' ' | '\'' | '-')) {
+        return;
+    }
+    if let Some(query) = lock().as_mut() {
+        query.push(c);
+    }
+}
+
+pub(crate) fn pop_char() {
+    if let Some(query) = lock().as_mut() {
+        query.pop();
+    }
+}
+
+/// Equip the best match to the right hand and stop searching.
+pub(crate) fn equip_top_match() {
+    let Some(query) = lock().take() else {
+        return;
+    };
+    // Equipping fires game events. Do not hold the lock meanwhile.
+    let item = {
+        let data = lock_wheeler();
+        find(&data, &query)
+            .first()
+            .and_then(|hit| item_at(&data, *hit))
+    };
+    super::activate(item, EquipHand::Right);
+}
+
+/// Items of all wheels matching `query`, best first.
+pub(super) fn find(data: &WheelerData, query: &str) -> Vec<SearchHit> {
+    let candidates = data.wheels.iter().enumerate().flat_map(|(wheel, page)| {
+        page.entries
+            .iter()
+            .enumerate()
+            .flat_map(move |(entry, slot)| {
+                slot.items.iter().enumerate().map(move |(item, content)| {
+                    let hit = SearchHit { wheel, entry, item };
+                    (hit, content.name())
+                })
+            })
+    });
+    fuzzy::rank(query, candidates)
+}
+
+fn item_at(data: &WheelerData, hit: SearchHit) -> Option<WheelItem> {
+    let entry = data.wheels.get(hit.wheel)?.entries.get(hit.entry)?;
+    entry.items.get(hit.item).cloned()
+}
+
+/// The query and the best results in the wheel center.
+pub(super) fn draw(
+    draw_list: *mut ImDrawList,
+    data: &WheelerData,
+    query: &str,
+    hits: &[SearchHit],
+    center: [f32; 2],
+    scale: f32,
+) {
+    let mut lines = vec![format!("Search: {query}_")];
+    lines.extend(hits.iter().take(MAX_SHOWN_RESULTS).filter_map(|hit| {
+        let name = item_at(data, *hit)?.name();
+        Some(match data.wheels.len() {
+            1 => name,
+            _ => format!("{name} ({})", hit.wheel + 1),
+        })
+    }));
+    if !query.is_empty() && hits.is_empty() {
+        lines.push("No match".to_string());
+    }
+
+    let font_size = unsafe { igGetFontSize() } * scale;
+    let line_height = calc_text_size(font_size, "A")[1];
+    let mut y = center[1] - line_height * lines.len() as f32 * 0.5;
+    for (index, line) in lines.iter().enumerate() {
+        let [width, _] = calc_text_size(font_size, line);
+        // The top match is equipped by Enter.
+        let color = match index {
+            1 => Color::WHITE,
+            _ => DIM,
+        };
+        draw_text(
+            draw_list,
+            [center[0] - width * 0.5, y],
+            font_size,
+            color,
+            line,
+        );
+        y += line_height;
+    }
+}
+
+fn lock() -> MutexGuard<'static, Option<String>> {
+    QUERY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,216 @@
+//! Window to change the keys of [`ControlsConfig`](crate::config::ControlsConfig) in game.
+//!
+//! ImGui gets no input from the game, so the window is driven by the keys of the wheel in
+//! [`BindContext::Settings`](crate::user_input::controls::BindContext::Settings).
+use core::ffi::CStr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::ffi::CString;
+
+use imgui::sys::{
+    ImGuiCond_FirstUseEver, ImVec2, igBegin, igButton, igEnd, igPopID, igPushID_Int, igSameLine,
+    igSeparator, igSetNextWindowSize,
+};
+
+use crate::config::{ControlsConfig, DeviceSlot};
+use crate::rendering::draw::text_unformatted;
+use crate::user_input::controls::Chord;
+use crate::user_input::input::Device;
+use crate::user_input::rebind::{self, Capture, Conflict};
+
+const SETTINGS_WINDOW_ID: &CStr = c"Wheeler Controls##Wheeler_rs_settings";
+static IS_OPEN: AtomicBool = AtomicBool::new(false);
+/// Row chosen with the keys. Index of [`ControlsConfig::ACTIONS`].
+static SELECTED_ROW: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+pub fn is_open() -> bool {
+    IS_OPEN.load(Ordering::Acquire)
+}
+
+/// Show the window, or hide it if shown.
+pub fn toggle() {
+    if IS_OPEN.fetch_xor(true, Ordering::AcqRel) {
+        rebind::cancel();
+    }
+}
+
+/// Hide the window and stop the capture.
+pub fn close() {
+    IS_OPEN.store(false, Ordering::Release);
+    rebind::cancel();
+}
+
+pub fn select_next_row() {
+    move_selection(1);
+}
+
+pub fn select_prev_row() {
+    move_selection(-1);
+}
+
+/// Move the row selection by `delta`, wrapping around.
+fn move_selection(delta: isize) {
+    let len = ControlsConfig::ACTIONS.len() as isize;
+    let _ = SELECTED_ROW.fetch_update(Ordering::AcqRel, Ordering::Acquire, |row| {
+        Some((row as isize + delta).rem_euclid(len) as usize)
+    });
+}
+
+/// Capture a key of the selected row for the slot of `device`, or bind the captured key
+/// despite its conflicts.
+pub fn activate_selected(device: Device) {
+    match rebind::current().is_some_and(|capture| capture.captured.is_some()) {
+        true => rebind::apply(),
+        false => rebind::start(selected_action(), slot_of(device)),
+    }
+}
+
+/// Unbind the key of the selected row for the slot of `device`.
+pub fn clear_selected(device: Device) {
+    rebind::clear(selected_action(), slot_of(device));
+}
+
+fn selected_action() -> &'static str {
+    let row = SELECTED_ROW.load(Ordering::Acquire);
+    ControlsConfig::ACTIONS[row % ControlsConfig::ACTIONS.len()]
+}
+
+/// The pad binds its own key, the keyboard and mouse the other.
+const fn slot_of(device: Device) -> DeviceSlot {
+    match device {
+        Device::Gamepad => DeviceSlot::Gamepad,
+        Device::Keyboard | Device::Mouse | Device::VirtualKeyboard => DeviceSlot::Keyboard,
+    }
+}
+
+/// Drawn inside the wheel popup, so that clicking the window doesn't close the popup.
+pub(super) fn draw(scale: f32) {
+    if !is_open() {
+        return;
+    }
+
+    // Copy the bindings, because the buttons write the config.
+    let rows: Vec<_> = {
+        let config = crate::config::get();
+        ControlsConfig::ACTIONS
+            .iter()
+            .map(|action| {
+                let binding = config.controls.binding(action);
+                let chord = |slot| binding.and_then(|binding| binding.chord(slot));
+                (
+                    *action,
+                    chord(DeviceSlot::Keyboard),
+                    chord(DeviceSlot::Gamepad),
+                )
+            })
+            .collect()
+    };
+
+    let mut is_open = true;
+    unsafe {
+        igSetNextWindowSize(
+            ImVec2::new(640.0 * scale, 560.0 * scale),
+            ImGuiCond_FirstUseEver as i32,
+        );
+        if igBegin(SETTINGS_WINDOW_ID.as_ptr(), &mut is_open, 0) {
+            draw_capture(rebind::current());
+            igSeparator();
+
+            let selected_row = SELECTED_ROW.load(Ordering::Acquire);
+            for (index, (action, keyboard, gamepad)) in rows.into_iter().enumerate() {
+                igPushID_Int(index as i32);
+                let marker = match index == selected_row {
+                    true => ">",
+                    false => " ",
+                };
+                text_unformatted(&format!("{marker} {action}"));
+                igSameLine(220.0 * scale, -1.0);
+                draw_slot(action, DeviceSlot::Keyboard, keyboard, scale);
+                igSameLine(430.0 * scale, -1.0);
+                draw_slot(action, DeviceSlot::Gamepad, gamepad, scale);
+                igPopID();
+            }
+        }
+        igEnd();
+    }
+
+    if !is_open {
+        close();
+    }
+}
+
+fn draw_capture(capture: Option<Capture>) {
+    let Some(capture) = capture else {
+        text_unformatted(
+            "`prev_item`/`next_item`, `prev_wheel`/`next_wheel` or the mouse wheel: select a row",
+        );
+        text_unformatted(
+            "`activate_right`: change the key of the pressing device, `remove_item`: unbind it",
+        );
+        return;
+    };
+    let device = match capture.slot {
+        DeviceSlot::Keyboard => "keyboard",
+        DeviceSlot::Gamepad => "gamepad",
+    };
+
+    let Some(captured) = capture.captured else {
+        text_unformatted(&format!(
+            "Press a {device} key for `{}`. Hold modifiers first for a chord. (Esc: cancel)",
+            capture.action
+        ));
+        return;
+    };
+
+    text_unformatted(&format!(
+        "`{}` for `{}` is already used:",
+        captured.chord, capture.action
+    ));
+    for conflict in &captured.conflicts {
+        match conflict {
+            Conflict::Action(action) => text_unformatted(&format!("- Wheeler `{action}`")),
+            Conflict::NumberKey { index, left_hand } => {
+                let hand = match left_hand {
+                    true => "left",
+                    false => "right",
+                };
+                text_unformatted(&format!(
+                    "- Wheeler number key of entry {} ({hand} hand)",
+                    index + 1
+                ));
+            }
+            Conflict::UserEvent(name) => text_unformatted(&format!("- Game `{name}`")),
+        }
+    }
+    text_unformatted("`activate_right`: bind anyway, `activate_left`: cancel");
+    unsafe {
+        if igButton(c"Bind anyway".as_ptr(), ImVec2::zero()) {
+            rebind::apply();
+        }
+        igSameLine(0.0, -1.0);
+        if igButton(c"Cancel".as_ptr(), ImVec2::zero()) {
+            rebind::cancel();
+        }
+    }
+}
+
+/// Button to capture the key, and a button to unbind it.
+fn draw_slot(action: &'static str, slot: DeviceSlot, chord: Option<Chord>, scale: f32) {
+    let label = chord.map_or_else(|| "-".to_string(), |chord| chord.to_string());
+    let Ok(label) = CString::new(format!("{label}##{slot:?}")) else {
+        return;
+    };
+    let Ok(clear_label) = CString::new(format!("x##{slot:?}")) else {
+        return;
+    };
+
+    unsafe {
+        if igButton(label.as_ptr(), ImVec2::new(170.0 * scale, 0.0)) {
+            rebind::start(action, slot);
+        }
+        igSameLine(0.0, -1.0);
+        if igButton(clear_label.as_ptr(), ImVec2::zero()) {
+            rebind::clear(action, slot);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Smart entries: resolve a [`ConsumableRule`] to an item of the inventory at activation.
+use core::ptr::NonNull;
+
+use commonlibsse_ng::re::ActorValues::ActorValue;
+use commonlibsse_ng::re::AlchemyItem::AlchemyItem;
+use commonlibsse_ng::re::BSCoreTypes::FormID;
+use commonlibsse_ng::re::EffectArchetypes::ArchetypeID;
+use commonlibsse_ng::re::FormTypes::FormType;
+use commonlibsse_ng::re::MagicItem::MagicItem;
+use commonlibsse_ng::re::TESAmmo::TESAmmo;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng_re_ext::re_ext::actor::get_player_missing_actor_value;
+use commonlibsse_ng_re_ext::re_ext::inventory::{get_player_entry_count, player_inventory_entries};
+use commonlibsse_ng_re_ext::re_ext::magic::{EffectInfo, get_magic_item_effects};
+
+use crate::config::{ConsumableKind, ConsumableRule, EffectKind, SortKey};
+use crate::rendering::texture_manager::IconImageType;
+
+/// The item of the player's inventory chosen by `rule` now.
+pub(crate) fn resolve(rule: &ConsumableRule) -> Option<FormID> {
+    let candidates: Vec<_> = player_inventory_entries()
+        .filter(|entry| get_player_entry_count(entry) > 0)
+        .filter_map(|entry| {
+            let form = entry.get_object()?.__base.__base.get_form();
+            Some((form.formID, strength(rule, form)?))
+        })
+        .collect();
+
+    let needed = match rule.sort {
+        SortKey::WeakestSufficient => rule
+            .effect
+            .and_then(restored_actor_value)
+            .and_then(get_player_missing_actor_value),
+        _ => None,
+    };
+    pick(&candidates, rule.sort, needed)
+}
+
+/// The first of `rules` that `form` matches. Used to add a smart entry from an example item.
+pub(crate) fn find_rule(rules: &[ConsumableRule], form: &TESForm) -> Option<ConsumableRule> {
+    rules
+        .iter()
+        .find(|rule| strength(rule, form).is_some())
+        .cloned()
+}
+
+/// Strength of `form` under `rule`, or `None` if it does not match.
+fn strength(rule: &ConsumableRule, form: &TESForm) -> Option<f32> {
+    match rule.kind {
+        ConsumableKind::Arrow | ConsumableKind::Bolt => {
+            if form.get_form_type() != FormType::Ammo {
+                return None;
+            }
+            let ammo = unsafe { NonNull::from(form).cast::<TESAmmo>().as_ref() }; // unsafe downcast
+            let is_bolt = ammo.is_bolt();
+            (is_bolt == (rule.kind == ConsumableKind::Bolt)).then_some(ammo.data.damage)
+        }
+        ConsumableKind::Potion | ConsumableKind::Poison | ConsumableKind::Food => {
+            if form.get_form_type() != FormType::AlchemyItem {
+                return None;
+            }
+            let alchemy = unsafe { NonNull::from(form).cast::<AlchemyItem>().as_ref() }; // unsafe downcast
+            let kind = match (alchemy.is_poison(), alchemy.is_food()) {
+                (true, _) => ConsumableKind::Poison,
+                (false, true) => ConsumableKind::Food,
+                (false, false) => ConsumableKind::Potion,
+            };
+            if kind != rule.kind {
+                return None;
+            }
+
+            let magic_item = unsafe { NonNull::from(form).cast::<MagicItem>().as_ref() }; // unsafe downcast
+            let effects = get_magic_item_effects(magic_item);
+            match rule.effect {
+                Some(effect) => effects
+                    .iter()
+                    .filter(|info| matches_effect(effect, info))
+                    .map(|info| info.magnitude)
+                    .reduce(|a, b| a + b),
+                None => Some(effects.iter().map(|info| info.magnitude).sum()),
+            }
+        }
+    }
+}
+
+fn matches_effect(effect: EffectKind, info: &EffectInfo) -> bool {
+    let helpful = |actor_value| !info.detrimental && info.actor_value == actor_value;
+    let restore = |actor_value| {
+        helpful(actor_value) && info.archetype == ArchetypeID::ValueModifier && info.duration == 0
+    };
+    let fortify = |actor_value| {
+        helpful(actor_value)
+            && (info.archetype == ArchetypeID::PeakValueModifier
+                || (info.archetype == ArchetypeID::ValueModifier && info.duration > 0))
+    };
+
+    match effect {
+        EffectKind::RestoreHealth => restore(ActorValue::Health),
+        EffectKind::RestoreMagicka => restore(ActorValue::Magicka),
+        EffectKind::RestoreStamina => restore(ActorValue::Stamina),
+        EffectKind::FortifyHealth => fortify(ActorValue::Health),
+        EffectKind::FortifyMagicka => fortify(ActorValue::Magicka),
+        EffectKind::FortifyStamina => fortify(ActorValue::Stamina),
+        EffectKind::ResistFire => helpful(ActorValue::ResistFire),
+        EffectKind::ResistFrost => helpful(ActorValue::ResistFrost),
+        EffectKind::ResistShock => helpful(ActorValue::ResistShock),
+        EffectKind::ResistMagic => helpful(ActorValue::ResistMagic),
+        EffectKind::ResistPoison => helpful(ActorValue::PoisonResist),
+        EffectKind::Invisibility => info.archetype == ArchetypeID::Invisibility,
+        EffectKind::WaterBreathing => helpful(ActorValue::WaterBreathing),
+        EffectKind::DamageHealth => {
+            info.detrimental
+                && info.actor_value == ActorValue::Health
+                && info.archetype == ArchetypeID::ValueModifier
+        }
+        EffectKind::Paralysis => info.archetype == ArchetypeID::Paralysis,
+    }
+}
+
+/// Actor value refilled by restore effects.
+const fn restored_actor_value(effect: EffectKind) -> Option<ActorValue> {
+    match effect {
+        EffectKind::RestoreHealth => Some(ActorValue::Health),
+        EffectKind::RestoreMagicka => Some(ActorValue::Magicka),
+        EffectKind::RestoreStamina => Some(ActorValue::Stamina),
+        _ => None,
+    }
+}
+
+/// - `candidates`: (form id, strength)
+/// - `needed`: lost amount, for [`SortKey::WeakestSufficient`]
+fn pick(candidates: &[(FormID, f32)], sort: SortKey, needed: Option<f32>) -> Option<FormID> {
+    let strongest = || candidates.iter().max_by(|a, b| a.1.total_cmp(&b.1));
+    let found = match (sort, needed) {
+        (SortKey::Strongest, _) | (SortKey::WeakestSufficient, None) => strongest(),
+        (SortKey::Weakest, _) => candidates.iter().min_by(|a, b| a.1.total_cmp(&b.1)),
+        (SortKey::WeakestSufficient, Some(needed)) => candidates
+            .iter()
+            .filter(|(_, strength)| *strength >= needed)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .or_else(strongest),
+    };
+    found.map(|(form_id, _)| *form_id)
+}
+
+/// Icon of the slot. The resolved item changes, so it is chosen by the rule.
+pub(crate) const fn icon_type(rule: &ConsumableRule) -> IconImageType {
+    match (rule.kind, rule.effect) {
+        (ConsumableKind::Arrow | ConsumableKind::Bolt, _) => IconImageType::Arrow,
+        (ConsumableKind::Food, _) => IconImageType::Food,
+        (ConsumableKind::Poison, _) => IconImageType::PoisonDefault,
+        (_, Some(EffectKind::RestoreHealth | EffectKind::FortifyHealth)) => {
+            IconImageType::PotionHealth
+        }
+        (_, Some(EffectKind::RestoreMagicka | EffectKind::FortifyMagicka)) => {
+            IconImageType::PotionMagicka
+        }
+        (_, Some(EffectKind::RestoreStamina | EffectKind::FortifyStamina)) => {
+            IconImageType::PotionStamina
+        }
+        (_, Some(EffectKind::ResistFire)) => IconImageType::PotionFireResist,
+        (_, Some(EffectKind::ResistFrost)) => IconImageType::PotionFrostResist,
+        (_, Some(EffectKind::ResistShock)) => IconImageType::PotionShockResist,
+        (_, Some(EffectKind::ResistMagic)) => IconImageType::PotionMagicResist,
+        _ => IconImageType::PotionDefault,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pick_by_sort() {
+        let candidates = [
+            (FormID::new(1), 25.0),
+            (FormID::new(2), 50.0),
+            (FormID::new(3), 100.0),
+        ];
+        use SortKey::{Strongest, Weakest, WeakestSufficient};
+
+        // (sort, needed, picked)
+        let table = [
+            (Strongest, None, 3),
+            (Strongest, Some(10.0), 3),
+            (Weakest, None, 1),
+            (Weakest, Some(40.0), 1),
+            (WeakestSufficient, Some(10.0), 1),
+            (WeakestSufficient, Some(40.0), 2),
+            (WeakestSufficient, Some(50.0), 2),
+            // None is enough, so the strongest.
+            (WeakestSufficient, Some(200.0), 3),
+            // Not a restore effect
+            (WeakestSufficient, None, 3),
+        ];
+        for (sort, needed, picked) in table {
+            assert_eq!(
+                (sort, needed, pick(&candidates, sort, needed)),
+                (sort, needed, Some(FormID::new(picked)))
+            );
+        }
+    }
+
+    #[test]
+    fn pick_from_nothing() {
+        for sort in [
+            SortKey::Strongest,
+            SortKey::Weakest,
+            SortKey::WeakestSufficient,
+        ] {
+            assert_eq!(pick(&[], sort, Some(10.0)), None);
+        }
+    }
+
+    const fn effect(
+        archetype: ArchetypeID,
+        actor_value: ActorValue,
+        detrimental: bool,
+        duration: u32,
+    ) -> EffectInfo {
+        EffectInfo {
+            archetype,
+            actor_value,
+            skill: ActorValue::None,
+            resist: ActorValue::None,
+            detrimental,
+            magnitude: 50.0,
+            duration,
+        }
+    }
+
+    #[test]
+    fn effect_kinds() {
+        use ActorValue::{Health, Magicka, ResistFire};
+        use ArchetypeID::{Invisibility, Paralysis, PeakValueModifier, ValueModifier};
+        use EffectKind as Kind;
+
+        let restore_health = effect(ValueModifier, Health, false, 0);
+        let regenerate_health = effect(ValueModifier, Health, false, 60);
+        let fortify_health = effect(PeakValueModifier, Health, false, 60);
+        let damage_health = effect(ValueModifier, Health, true, 0);
+        let restore_magicka = effect(ValueModifier, Magicka, false, 0);
+        let resist_fire = effect(PeakValueModifier, ResistFire, false, 60);
+        let weakness_to_fire = effect(PeakValueModifier, ResistFire, true, 60);
+        let invisibility = effect(Invisibility, Health, false, 30);
+        let paralysis = effect(Paralysis, Health, true, 10);
+
+        // (effect, kind, matches)
+        let table = [
+            (restore_health, Kind::RestoreHealth, true),
+            (restore_health, Kind::FortifyHealth, false),
+            (restore_health, Kind::RestoreMagicka, false),
+            // A restore with a duration raises the value over time, like a fortify.
+            (regenerate_health, Kind::RestoreHealth, false),
+            (regenerate_health, Kind::FortifyHealth, true),
+            (fortify_health, Kind::FortifyHealth, true),
+            (fortify_health, Kind::RestoreHealth, false),
+            (damage_health, Kind::RestoreHealth, false),
+            (damage_health, Kind::DamageHealth, true),
+            (restore_health, Kind::DamageHealth, false),
+            (restore_magicka, Kind::RestoreMagicka, true),
+            (resist_fire, Kind::ResistFire, true),
+            (weakness_to_fire, Kind::ResistFire, false),
+            (invisibility, Kind::Invisibility, true),
+            (invisibility, Kind::FortifyHealth, false),
+            (paralysis, Kind::Paralysis, true),
+            (paralysis, Kind::DamageHealth, false),
+        ];
+        for (index, (info, kind, matches)) in table.into_iter().enumerate() {
+            assert_eq!(
+                (index, kind, matches_effect(kind, &info)),
+                (index, kind, matches)
+            );
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Weapon and armor stats of the description panel.
+use core::ptr::NonNull;
+
+use commonlibsse_ng::re::EnchantmentItem::EnchantmentItem;
+use commonlibsse_ng::re::ExtraDataList::ExtraDataList;
+use commonlibsse_ng::re::FormTypes::FormType;
+use commonlibsse_ng::re::TESForm::TESForm;
+use commonlibsse_ng::re::TESObjectARMO::TESObjectARMO;
+use commonlibsse_ng::re::TESObjectWEAP::TESObjectWEAP;
+use commonlibsse_ng_re_ext::re_ext::inventory::{
+    find_extra_data_list_by_unique_id, find_player_inventory_entry, get_enchantable_form,
+    get_entry_enchant_and_health, get_extra_list_charge, get_extra_list_enchant_and_health,
+};
+use commonlibsse_ng_re_ext::re_ext::setting::get_game_setting_string;
+
+use super::item::form_name;
+use super::tooltip::StatLine;
+use crate::rendering::texture_manager::IconImageType;
+
+/// Damage or armor rating added per tier. Approximate: the game also scales it by the smithing
+/// perks of the player, so the shown bonus is marked with `~`.
+const TEMPER_BONUS_PER_TIER: f32 = 1.0;
+
+/// Quality of a tempered weapon or armor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TemperTier {
+    Fine,
+    Superior,
+    Exquisite,
+    Flawless,
+    Epic,
+    Legendary,
+}
+
+impl TemperTier {
+    /// `health`: `ExtraHealth::health`. `1.0` is not tempered, then `+0.1` per tier, rounded to the
+    /// nearest tier.
+    pub fn from_health(health: f32) -> Option<Self> {
+        let tier = ((health - 1.0) * 10.0).round();
+        Some(match tier {
+            tier if tier.is_nan() || tier < 1.0 => return None,
+            tier if tier < 2.0 => Self::Fine,
+            tier if tier < 3.0 => Self::Superior,
+            tier if tier < 4.0 => Self::Exquisite,
+            tier if tier < 5.0 => Self::Flawless,
+            tier if tier < 6.0 => Self::Epic,
+            _ => Self::Legendary,
+        })
+    }
+
+    /// `Fine` is 1.
+    pub const fn level(self) -> u8 {
+        self as u8 + 1
+    }
+
+    /// Name in the game language. (`sHealthDataPrefixWeap1`-`6` or `sHealthDataPrefixArmo1`-`6`)
+    pub fn label(self, is_armor: bool) -> String {
+        let kind = match is_armor {
+            true => "Armo",
+            false => "Weap",
+        };
+        let setting = format!("sHealthDataPrefix{kind}{}", self.level());
+        game_text(&setting, self.english_label())
+    }
+
+    const fn english_label(self) -> &'static str {
+        match self {
+            Self::Fine => "Fine",
+            Self::Superior => "Superior",
+            Self::Exquisite => "Exquisite",
+            Self::Flawless => "Flawless",
+            Self::Epic => "Epic",
+            Self::Legendary => "Legendary",
+        }
+    }
+}
+
+/// Stat lines of a weapon or an armor. Other forms have no stats.
+///
+/// - `unique_id`: read tempering and enchantment of this instance. If `None`, of any instance.
+pub(crate) fn item_stats(form: &TESForm, unique_id: Option<u16>) -> Vec<StatLine> {
+    let (icon, base_value, is_armor) = match form.get_form_type() {
+        FormType::Weapon => {
+            let weapon = unsafe { NonNull::from(form).cast::<TESObjectWEAP>().as_ref() }; // unsafe downcast
+            (
+                IconImageType::WeaponDamage,
+                f32::from(weapon.__base7.attackDamage),
+                false,
+            )
+        }
+        FormType::Armor => {
+            let armor = unsafe { NonNull::from(form).cast::<TESObjectARMO>().as_ref() }; // unsafe downcast
+            // Stored x100
+            (
+                IconImageType::ArmorRating,
+                armor.armorRating as f32 / 100.0,
+                true,
+            )
+        }
+        _ => return Vec::new(),
+    };
+
+    let label = match is_armor {
+        true => game_text("sArmor", "Armor"),
+        false => game_text("sDamage", "Damage"),
+    };
+    let instance = Instance::find(form, unique_id);
+    let tier = instance.health.and_then(TemperTier::from_health);
+
+    let mut stats = Vec::new();
+    let value = match tier {
+        Some(tier) => {
+            let bonus = f32::from(tier.level()) * TEMPER_BONUS_PER_TIER;
+            format!(
+                "{label}: ~{:.0} ({}, ~+{bonus:.0})",
+                base_value + bonus,
+                tier.label(is_armor)
+            )
+        }
+        None => format!("{label}: {base_value:.0}"),
+    };
+    stats.push(StatLine::with_icon(icon, value));
+
+    let enchantment = instance.enchantment.or_else(|| {
+        get_enchantable_form(form).and_then(|enchantable| NonNull::new(enchantable.formEnchanting))
+    });
+    if let Some(enchantment) = enchantment {
+        let name = form_name(unsafe { enchantment.cast::<TESForm>().as_ref() });
+        stats.push(StatLine::new(name));
+
+        let max_charge = instance.max_charge.or_else(|| {
+            get_enchantable_form(form).map(|enchantable| enchantable.amountofEnchantment)
+        });
+        // Armor enchantments have no charge.
+        if let Some(charge) = charge_text(instance.charge, max_charge) {
+            stats.push(StatLine::new(charge));
+        }
+    }
+
+    stats
+}
+
+/// Game setting `setting` in the game language, or `english` if missing.
+fn game_text(setting: &str, english: &str) -> String {
+    get_game_setting_string(setting).unwrap_or_else(|| english.to_string())
+}
+
+/// Tempering and enchantment of an item in the player's inventory.
+#[derive(Debug, Default)]
+struct Instance {
+    enchantment: Option<NonNull<EnchantmentItem>>,
+    health: Option<f32>,
+    charge: Option<f32>,
+    max_charge: Option<u16>,
+}
+
+impl Instance {
+    fn find(form: &TESForm, unique_id: Option<u16>) -> Self {
+        let Some(entry) = find_player_inventory_entry(form.formID) else {
+            return Self::default();
+        };
+
+        match unique_id.and_then(|unique_id| find_extra_data_list_by_unique_id(entry, unique_id)) {
+            Some(extra_list) => Self::from_extra_list(extra_list),
+            None => {
+                let (enchantment, health) = get_entry_enchant_and_health(entry);
+                let (charge, max_charge) = entry
+                    .extraLists
+                    .as_ref()
+                    .and_then(|extra_lists| {
+                        extra_lists
+                            .iter()
+                            .map(get_extra_list_charge)
+                            .find(|(charge, _)| charge.is_some())
+                    })
+                    .unwrap_or_default();
+                Self {
+                    enchantment,
+                    health,
+                    charge,
+                    max_charge,
+                }
+            }
+        }
+    }
+
+    fn from_extra_list(extra_list: &ExtraDataList) -> Self {
+        let (enchantment, health) = get_extra_list_enchant_and_health(extra_list);
+        let (charge, max_charge) = get_extra_list_charge(extra_list);
+        Self {
+            enchantment,
+            health,
+            charge,
+            max_charge,
+        }
+    }
+}
+
+/// Remaining charge (`0.0..=1.0`) of an enchanted weapon.
+pub(crate) fn enchantment_charge(form: &TESForm, unique_id: Option<u16>) -> Option<f32> {
+    let enchantable = get_enchantable_form(form)?;
+    let instance = Instance::find(form, unique_id);
+    if instance.enchantment.is_none() && enchantable.formEnchanting.is_null() {
+        return None;
+    }
+
+    let max_charge = instance
+        .max_charge
+        .or(Some(enchantable.amountofEnchantment));
+    charge_ratio(instance.charge, max_charge)
+}
+
+/// `charge`: `None` means never used, so it is full.
+fn charge_ratio(charge: Option<f32>, max_charge: Option<u16>) -> Option<f32> {
+    let max_charge = f32::from(max_charge.filter(|max_charge| *max_charge > 0)?);
+    let charge = charge.unwrap_or(max_charge).clamp(0.0, max_charge);
+    Some(charge / max_charge)
+}
+
+fn charge_text(charge: Option<f32>, max_charge: Option<u16>) -> Option<String> {
+    let ratio = charge_ratio(charge, max_charge)?;
+    Some(format!("Charge: {:.0}%", ratio * 100.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn temper_tier_boundaries() {
+        let cases = [
+            (f32::NAN, None),
+            (0.5, None),
+            (1.0, None),
+            (1.04, None),
+            (1.06, Some(TemperTier::Fine)),
+            (1.1, Some(TemperTier::Fine)),
+            (1.14, Some(TemperTier::Fine)),
+            (1.16, Some(TemperTier::Superior)),
+            (1.2, Some(TemperTier::Superior)),
+            (1.3, Some(TemperTier::Exquisite)),
+            (1.4, Some(TemperTier::Flawless)),
+            (1.5, Some(TemperTier::Epic)),
+            (1.54, Some(TemperTier::Epic)),
+            (1.56, Some(TemperTier::Legendary)),
+            (1.6, Some(TemperTier::Legendary)),
+            (3.0, Some(TemperTier::Legendary)),
+        ];
+        for (health, expected) in cases {
+            assert_eq!(TemperTier::from_health(health), expected, "{health}");
+        }
+        assert_eq!(TemperTier::Fine.level(), 1);
+        assert_eq!(TemperTier::Legendary.level(), 6);
+    }
+}
@@ -0,0 +1,138 @@
+use core::ffi::CStr;
+
+use imgui::sys::{
+    ImGuiCond_Always, ImGuiWindowFlags_NoDecoration, ImGuiWindowFlags_NoFocusOnAppearing,
+    ImGuiWindowFlags_NoInputs, ImGuiWindowFlags_NoNav, ImGuiWindowFlags_NoSavedSettings, ImVec2,
+    igBegin, igEnd, igGetTextLineHeight, igPopTextWrapPos, igPushTextWrapPos, igSameLine,
+    igSeparator, igSetNextWindowBgAlpha, igSetNextWindowPos, igSetNextWindowSize,
+};
+
+use super::wheel::OUTER_RADIUS;
+use crate::config::{TooltipAnchor, TooltipConfig};
+use crate::rendering::color::Color;
+use crate::rendering::draw::{draw_image_inline, text_unformatted};
+use crate::rendering::rich_text::{RichText, draw_rich_text};
+use crate::rendering::texture_manager::{IconImageType, IconState, get_tinted_icon_image};
+
+const TOOLTIP_WINDOW_ID: &CStr = c"##Wheeler_rs_tooltip";
+
+/// Text of the description panel.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TooltipContent {
+    pub title: String,
+    /// Effect description without format codes.
+    pub description: Option<String>,
+    /// Effect description with colors. Falls back to `description` if `None` or disabled.
+    pub rich_description: Option<RichText>,
+    pub stats: Vec<StatLine>,
+}
+
+/// A line of stats. (e.g. damage, enchantment)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatLine {
+    /// Drawn in front of the text.
+    pub icon: Option<IconImageType>,
+    pub text: String,
+}
+
+impl StatLine {
+    #[inline]
+    pub const fn new(text: String) -> Self {
+        Self { icon: None, text }
+    }
+
+    #[inline]
+    pub const fn with_icon(icon: IconImageType, text: String) -> Self {
+        Self {
+            icon: Some(icon),
+            text,
+        }
+    }
+}
+
+/// Draw the description panel next to the wheel.
+///
+/// - `wheel_center`: screen position
+/// - `scale`: resolution scale. (config values are for 1080p)
+pub(crate) fn draw(
+    content: &TooltipContent,
+    wheel_center: [f32; 2],
+    scale: f32,
+    config: &TooltipConfig,
+) {
+    if !config.enabled {
+        return;
+    }
+
+    let [offset_x, offset_y] = config.offset.map(|offset| offset * scale);
+    let (pos, pivot) = match config.anchor {
+        TooltipAnchor::WheelRight => (
+            [
+                wheel_center[0] + OUTER_RADIUS * scale + offset_x,
+                wheel_center[1] + offset_y,
+            ],
+            [0.0, 0.5],
+        ),
+        TooltipAnchor::WheelLeft => (
+            [
+                wheel_center[0] - OUTER_RADIUS * scale - offset_x,
+                wheel_center[1] + offset_y,
+            ],
+            [1.0, 0.5],
+        ),
+        TooltipAnchor::Fixed => ([offset_x, offset_y], [0.0, 0.0]),
+    };
+    // 0.0 means auto fit. Without wrapping, the width follows the longest line.
+    let width = if config.wrap {
+        config.width * scale
+    } else {
+        0.0
+    };
+
+    let flags = ImGuiWindowFlags_NoDecoration
+        | ImGuiWindowFlags_NoInputs
+        | ImGuiWindowFlags_NoSavedSettings
+        | ImGuiWindowFlags_NoFocusOnAppearing
+        | ImGuiWindowFlags_NoNav;
+
+    unsafe {
+        igSetNextWindowPos(pos.into(), ImGuiCond_Always as i32, pivot.into());
+        igSetNextWindowSize(ImVec2::new(width, 0.0), ImGuiCond_Always as i32);
+        igSetNextWindowBgAlpha(config.background_alpha);
+
+        if igBegin(
+            TOOLTIP_WINDOW_ID.as_ptr(),
+            core::ptr::null_mut(),
+            flags as i32,
+        ) {
+            // 0.0: wrap at the end of the window, negative: no wrapping
+            igPushTextWrapPos(if config.wrap { 0.0 } else { -1.0 });
+
+            text_unformatted(&content.title);
+            if content.description.is_some() || !content.stats.is_empty() {
+                igSeparator();
+            }
+            match (&content.rich_description, &content.description) {
+                (Some(rich_description), _) if config.rich_text => {
+                    draw_rich_text(rich_description, config.wrap);
+                }
+                (_, Some(description)) => text_unformatted(description),
+                _ => {}
+            }
+            for stat in &content.stats {
+                let icon = stat
+                    .icon
+                    .and_then(|icon| get_tinted_icon_image(icon, None, IconState::Normal, None));
+                if let Some(icon) = icon {
+                    let size = igGetTextLineHeight();
+                    draw_image_inline(&icon, [size, size], icon.tint.unwrap_or(Color::WHITE));
+                    igSameLine(0.0, -1.0);
+                }
+                text_unformatted(&stat.text);
+            }
+
+            igPopTextWrapPos();
+        }
+        igEnd();
+    }
+}
@@ -0,0 +1,327 @@
+use core::f32::consts::{FRAC_PI_2, TAU};
+
+use imgui::sys::{ImDrawList, igGetFontSize};
+
+use super::badge;
+use super::entry::WheelEntry;
+use crate::config::{BadgeConfig, NumberKeysConfig};
+use crate::rendering::color::Color;
+use crate::rendering::draw::{calc_text_size, draw_image, draw_text};
+use crate::rendering::texture_manager::{IconImageType, IconState, get_tinted_icon_image};
+
+// NOTE: Sizes are for 1080p. Multiply the resolution scale.
+pub(crate) const OUTER_RADIUS: f32 = 360.0;
+pub(crate) const INNER_RADIUS: f32 = 240.0;
+/// Slot centers are on this circle.
+const SLOT_RADIUS: f32 = (OUTER_RADIUS + INNER_RADIUS) * 0.5;
+const SLOT_SIZE: f32 = 110.0;
+const ICON_SIZE: f32 = 72.0;
+/// Multiplied to the default font size.
+const POSITION_LABEL_FONT_SCALE: f32 = 0.8;
+/// Gap between the wheel and the indicator row.
+const INDICATOR_MARGIN: f32 = 24.0;
+const INDICATOR_SIZE: f32 = 16.0;
+const INDICATOR_SPACING: f32 = 8.0;
+/// Cursor within this radius does not select any entry.
+const CURSOR_DEADZONE: f32 = 40.0;
+/// The cursor cannot go out of this radius.
+pub(crate) const CURSOR_MAX_RADIUS: f32 = OUTER_RADIUS;
+
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Wheel {
+    /// Shown under the indicator row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub entries: Vec<WheelEntry>,
+    #[serde(skip)]
+    hovered: Option<usize>,
+}
+
+impl Wheel {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            name: None,
+            entries: Vec::new(),
+            hovered: None,
+        }
+    }
+
+    #[inline]
+    pub const fn hovered_index(&self) -> Option<usize> {
+        self.hovered
+    }
+
+    #[inline]
+    pub fn hovered_entry(&self) -> Option<&WheelEntry> {
+        self.entries.get(self.hovered?)
+    }
+
+    #[inline]
+    pub fn hovered_entry_mut(&mut self) -> Option<&mut WheelEntry> {
+        self.entries.get_mut(self.hovered?)
+    }
+
+    /// `cursor`: offset from the wheel center. (1080p)
+    pub(crate) fn update_hovered(&mut self, cursor: [f32; 2]) {
+        self.hovered = entry_index_at(cursor, self.entries.len());
+    }
+
+    /// - `matched_entries`: entries with a search match. Highlighted like the hovered one.
+    pub(crate) fn draw(
+        &self,
+        draw_list: *mut ImDrawList,
+        center: [f32; 2],
+        scale: f32,
+        badge_config: &BadgeConfig,
+        show_numbers: bool,
+        matched_entries: &[usize],
+    ) {
+        if let Some(background) = get_tinted_icon_image(
+            IconImageType::WheelBackground,
+            None,
+            IconState::Normal,
+            None,
+        ) {
+            let size = OUTER_RADIUS * 2.0 * scale;
+            draw_image(draw_list, &background, center, [size, size], Color::WHITE);
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let is_hovered = self.hovered == Some(index);
+            let slot_center = slot_center(center, scale, index, self.entries.len());
+
+            let item = entry.current_item();
+            // The slot follows the state of its item, so that e.g. equipped slots stand out.
+            let state = match item {
+                Some(item) => item.icon_state(is_hovered),
+                None if is_hovered => IconState::Hovered,
+                None => IconState::Normal,
+            };
+
+            let background = match is_hovered || matched_entries.contains(&index) {
+                true => IconImageType::SlotHighlightedBackground,
+                false => IconImageType::SlotBackground,
+            };
+            if let Some(image) = get_tinted_icon_image(background, None, state, None) {
+                let size = SLOT_SIZE * scale;
+                draw_image(draw_list, &image, slot_center, [size, size], Color::WHITE);
+            }
+            if show_numbers {
+                if let Some(label) = NumberKeysConfig::label(index) {
+                    draw_number_label(draw_list, &label, center, slot_center, scale);
+                }
+            }
+
+            let Some(item) = item else {
+                continue;
+            };
+            if let Some(icon) =
+                get_tinted_icon_image(item.icon_type(), item.form(), state, item.element())
+            {
+                let size = ICON_SIZE * scale;
+                let tint = icon.tint.unwrap_or(Color::WHITE);
+                draw_image(draw_list, &icon, slot_center, [size, size], tint);
+            }
+            if let Some(label) = entry.position_label() {
+                draw_position_label(draw_list, &label, slot_center, scale);
+            }
+            badge::draw(
+                draw_list,
+                item,
+                slot_center,
+                SLOT_SIZE * scale,
+                scale,
+                badge_config,
+            );
+        }
+    }
+}
+
+/// `"2/4"` centered on the top edge of the slot.
+fn draw_position_label(draw_list: *mut ImDrawList, label: &str, slot_center: [f32; 2], scale: f32) {
+    let font_size = unsafe { igGetFontSize() } * POSITION_LABEL_FONT_SCALE * scale;
+    let [width, height] = calc_text_size(font_size, label);
+    let top = slot_center[1] - SLOT_SIZE * scale * 0.5;
+    let pos = [slot_center[0] - width * 0.5, top - height * 0.5];
+    draw_text(draw_list, pos, font_size, Color::WHITE, label);
+}
+
+/// Number key of the slot, on the inner side toward the wheel center.
+fn draw_number_label(
+    draw_list: *mut ImDrawList,
+    label: &str,
+    center: [f32; 2],
+    slot_center: [f32; 2],
+    scale: f32,
+) {
+    let font_size = unsafe { igGetFontSize() } * POSITION_LABEL_FONT_SCALE * scale;
+    let [width, height] = calc_text_size(font_size, label);
+    let [dx, dy] = [slot_center[0] - center[0], slot_center[1] - center[1]];
+    let ratio = (SLOT_RADIUS - SLOT_SIZE * 0.5) / SLOT_RADIUS;
+    let pos = [
+        center[0] + dx * ratio - width * 0.5,
+        center[1] + dy * ratio - height * 0.5,
+    ];
+    draw_text(draw_list, pos, font_size, Color::WHITE, label);
+}
+
+/// One dot per wheel under the wheel, and the name of the active wheel under them.
+///
+/// Nothing is drawn for a single unnamed wheel.
+pub(crate) fn draw_indicators(
+    draw_list: *mut ImDrawList,
+    center: [f32; 2],
+    scale: f32,
+    active: usize,
+    len: usize,
+    name: Option<&str>,
+) {
+    let size = INDICATOR_SIZE * scale;
+    let spacing = INDICATOR_SPACING * scale;
+    let y = center[1] + (OUTER_RADIUS + INDICATOR_MARGIN) * scale + size * 0.5;
+
+    if len > 1 {
+        let row_width = size * len as f32 + spacing * (len - 1) as f32;
+        let left = center[0] - row_width * 0.5 + size * 0.5;
+
+        for index in 0..len {
+            let image_type = match index == active {
+                true => IconImageType::WheelIndicatorActive,
+                false => IconImageType::WheelIndicatorInactive,
+            };
+            if let Some(image) = get_tinted_icon_image(image_type, None, IconState::Normal, None) {
+                let x = left + (size + spacing) * index as f32;
+                draw_image(draw_list, &image, [x, y], [size, size], Color::WHITE);
+            }
+        }
+    }
+
+    if let Some(name) = name {
+        let font_size = unsafe { igGetFontSize() } * scale;
+        let [width, _] = calc_text_size(font_size, name);
+        let pos = [center[0] - width * 0.5, y + size * 0.5 + spacing];
+        draw_text(draw_list, pos, font_size, Color::WHITE, name);
+    }
+}
+
+/// Shown above the wheel while editing.
+pub(crate) fn draw_edit_mode_label(draw_list: *mut ImDrawList, center: [f32; 2], scale: f32) {
+    const LABEL: &str = "Edit mode";
+
+    let font_size = unsafe { igGetFontSize() } * scale;
+    let [width, height] = calc_text_size(font_size, LABEL);
+    let pos = [
+        center[0] - width * 0.5,
+        center[1] - (OUTER_RADIUS + INDICATOR_MARGIN) * scale - height,
+    ];
+    draw_text(draw_list, pos, font_size, Color::WHITE, LABEL);
+}
+
+/// Screen position of the `index`th slot.
+pub(crate) fn slot_center(center: [f32; 2], scale: f32, index: usize, len: usize) -> [f32; 2] {
+    let angle = entry_angle(index, len);
+    let radius = SLOT_RADIUS * scale;
+    [
+        center[0] + radius * angle.cos(),
+        center[1] + radius * angle.sin(),
+    ]
+}
+
+/// The first entry is at the top, then clockwise. (screen y axis is down)
+#[inline]
+fn entry_angle(index: usize, len: usize) -> f32 {
+    -FRAC_PI_2 + TAU * index as f32 / len.max(1) as f32
+}
+
+/// Which entry does `cursor`(offset from the center) point?
+pub(crate) fn entry_index_at(cursor: [f32; 2], len: usize) -> Option<usize> {
+    if len == 0 || cursor[0].hypot(cursor[1]) < CURSOR_DEADZONE {
+        return None;
+    }
+
+    let sector = TAU / len as f32;
+    // Rotate so that the first entry is at 0 rad, then shift half a sector to round to the nearest.
+    let angle = (cursor[1].atan2(cursor[0]) + FRAC_PI_2 + sector * 0.5).rem_euclid(TAU);
+    Some((angle / sector) as usize % len)
+}
+
+/// Clamp `cursor` into the wheel.
+pub(crate) fn clamp_cursor(cursor: [f32; 2]) -> [f32; 2] {
+    let len = cursor[0].hypot(cursor[1]);
+    if len <= CURSOR_MAX_RADIUS {
+        return cursor;
+    }
+    let ratio = CURSOR_MAX_RADIUS / len;
+    [cursor[0] * ratio, cursor[1] * ratio]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn entry_index_at_directions() {
+        // (cursor, len, expected)
+        let cases = [
+            ([0.0, -100.0], 0, None),
+            ([0.0, 0.0], 4, None),
+            ([0.0, -(CURSOR_DEADZONE - 1.0)], 4, None),
+            ([0.0, -CURSOR_DEADZONE], 4, Some(0)),
+            ([0.0, -100.0], 1, Some(0)),
+            ([0.0, 100.0], 1, Some(0)),
+            // up, right, down and left
+            ([0.0, -100.0], 4, Some(0)),
+            ([100.0, 0.0], 4, Some(1)),
+            ([0.0, 100.0], 4, Some(2)),
+            ([-100.0, 0.0], 4, Some(3)),
+            // Around the border of the sectors 0 and 1. (up-right)
+            ([99.0, -101.0], 4, Some(0)),
+            ([101.0, -99.0], 4, Some(1)),
+            // Around the border of the last and the first sectors. (up-left)
+            ([-99.0, -101.0], 4, Some(0)),
+            ([-101.0, -99.0], 4, Some(3)),
+            // Out of the wheel still selects.
+            ([0.0, 1000.0], 4, Some(2)),
+        ];
+        for (cursor, len, expected) in cases {
+            assert_eq!(entry_index_at(cursor, len), expected, "{cursor:?}, {len}");
+        }
+    }
+
+    #[test]
+    fn entry_index_at_slot_centers() {
+        for len in 1..=12 {
+            for index in 0..len {
+                let [x, y] = slot_center([0.0, 0.0], 1.0, index, len);
+                assert_eq!(entry_index_at([x, y], len), Some(index), "{index}/{len}");
+            }
+        }
+    }
+
+    #[test]
+    fn clamp_cursor_to_wheel() {
+        let cases = [
+            ([0.0, 0.0], [0.0, 0.0]),
+            ([100.0, -100.0], [100.0, -100.0]),
+            ([0.0, CURSOR_MAX_RADIUS], [0.0, CURSOR_MAX_RADIUS]),
+            ([0.0, CURSOR_MAX_RADIUS * 2.0], [0.0, CURSOR_MAX_RADIUS]),
+            ([-CURSOR_MAX_RADIUS * 3.0, 0.0], [-CURSOR_MAX_RADIUS, 0.0]),
+            // 3:4:5, keeping the direction
+            (
+                [3000.0, -4000.0],
+                [CURSOR_MAX_RADIUS * 0.6, -CURSOR_MAX_RADIUS * 0.8],
+            ),
+        ];
+        for (cursor, expected) in cases {
+            let [x, y] = clamp_cursor(cursor);
+            assert!(
+                (x - expected[0]).abs() < 1e-3 && (y - expected[1]).abs() < 1e-3,
+                "{cursor:?} -> {:?}, expected {expected:?}",
+                [x, y]
+            );
+        }
+    }
+}
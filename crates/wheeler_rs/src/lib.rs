@@ -23,17 +23,54 @@ fn plugin_main() {
     };
 
     bail!(messaging.register_skse_listener(skse_event_listener));
+    register_serialization();
     on_skse_init();
 }
 
 fn skse_event_listener(message: &Message) {
     if let Some(msg_type) = message.msg_type.to_enum() {
-        if msg_type == MessageType::PostLoadGame {}
+        match msg_type {
+            MessageType::DataLoaded => {
+                if wheeler_core::wheeler::install_inventory_watcher().is_none() {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Failed to watch the inventory. Slot counts may be outdated.");
+                }
+            }
+            MessageType::PostLoadGame => wheeler_core::wheeler::invalidate_badges(),
+            _ => {}
+        }
     }
 }
 
+/// Wheels are saved per save file. Without this, wheels work but are lost on exit.
+fn register_serialization() {
+    use wheeler_core::wheeler::persistence;
+
+    let serialization = match skse::api::get_serialization_interface() {
+        Ok(serialization) => serialization,
+        Err(_err) => {
+            #[cfg(feature = "tracing")]
+            tracing::error!("Failed to get serialization interface: {_err}");
+            return;
+        }
+    };
+
+    serialization.set_unique_id(persistence::SERIALIZATION_ID);
+    serialization.set_save_callback(persistence::on_save);
+    serialization.set_load_callback(persistence::on_load);
+    serialization.set_revert_callback(persistence::on_revert);
+}
+
 fn on_skse_init() {
+    // Missing or broken config is not fatal. Continue with the default config.
+    if let Err(_err) = wheeler_core::config::load() {
+        #[cfg(feature = "tracing")]
+        tracing::error!("{_err}");
+    }
+    wheeler_core::user_input::actions::bind_from_config();
+
     bail!(wheeler_core::rendering::render_manager::install());
+    bail!(wheeler_core::user_input::hook::install());
 }
 
 // This is because CI will treat it as an error if there is at least one test missing.